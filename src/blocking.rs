@@ -0,0 +1,450 @@
+//! Blocking interface.
+//!
+//! This provides a blocking driver with the same conversion and
+//! configuration logic as the async driver. It is enabled through one of
+//! these features, which select the I²C traits the bus must implement:
+//! - `eh02`: `embedded-hal` 0.2 blocking I²C traits (`Read`, `Write` and
+//!   `WriteRead`).
+//! - `eh1`: `embedded-hal` 1.0 I²C trait (`I2c`).
+//!
+//! The example uses `linux-embedded-hal` 0.3, which implements the
+//! `embedded-hal` 0.2 traits, so it only builds with the `eh02` feature.
+//!
+#![cfg_attr(feature = "eh02", doc = "```no_run")]
+#![cfg_attr(not(feature = "eh02"), doc = "```ignore")]
+//! use linux_embedded_hal::I2cdev;
+//! use tmp1x2::{blocking::Tmp1x2, SlaveAddr};
+//!
+//! let dev = I2cdev::new("/dev/i2c-1").unwrap();
+//! let mut sensor = Tmp1x2::new(dev, SlaveAddr::default());
+//! let temperature = sensor.read_temperature().unwrap();
+//! ```
+
+use crate::bus::{needs_pointer, register_data, register_write, REGISTER_READ_LEN};
+use crate::conversion::convert_temp_from_register;
+use crate::reading::{fixed_bits_match, is_alert_active};
+use crate::{
+    marker::mode, AlertPolarity, BitFlagsHigh as BFH, BitFlagsLow as BFL, Config, ConversionRate,
+    DeviceKind, Error, FaultQueue, Mode, ModeChangeError, Register, SlaveAddr, ThermostatMode,
+    DEVICE_BASE_ADDRESS, EXTENDED_MAX_C, EXTENDED_MIN_C, NORMAL_MAX_C, NORMAL_MIN_C,
+};
+use core::marker::PhantomData;
+
+#[cfg(all(feature = "eh02", feature = "eh1"))]
+compile_error!("The features `eh02` and `eh1` cannot be enabled at the same time.");
+
+/// Blocking I²C bus operations needed by the driver.
+///
+/// This is implemented for all types implementing the `embedded-hal` 0.2
+/// `Read`, `Write` and `WriteRead` traits with the `eh02` feature, or the
+/// `embedded-hal` 1.0 `I2c` trait with the `eh1` feature.
+pub trait I2c {
+    /// Bus error type.
+    type Error;
+
+    /// Read bytes from the device.
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Write bytes to the device.
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Write bytes to the device and read bytes back in a single transaction.
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+#[cfg(feature = "eh02")]
+impl<T, E> I2c for T
+where
+    T: embedded_hal::blocking::i2c::Read<Error = E>
+        + embedded_hal::blocking::i2c::Write<Error = E>
+        + embedded_hal::blocking::i2c::WriteRead<Error = E>,
+{
+    type Error = E;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), E> {
+        embedded_hal::blocking::i2c::Read::read(self, address, buffer)
+    }
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), E> {
+        embedded_hal::blocking::i2c::Write::write(self, address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), E> {
+        embedded_hal::blocking::i2c::WriteRead::write_read(self, address, bytes, buffer)
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<T> I2c for T
+where
+    T: embedded_hal_1::i2c::I2c,
+{
+    type Error = T::Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), T::Error> {
+        embedded_hal_1::i2c::I2c::read(self, address, buffer)
+    }
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), T::Error> {
+        embedded_hal_1::i2c::I2c::write(self, address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), T::Error> {
+        embedded_hal_1::i2c::I2c::write_read(self, address, bytes, buffer)
+    }
+}
+
+/// TMP1X2 blocking device driver.
+#[derive(Debug)]
+pub struct Tmp1x2<I2C, MODE> {
+    /// The concrete I²C device implementation.
+    i2c: I2C,
+    /// The I²C device address.
+    address: u8,
+    /// Configuration register status.
+    config: Config,
+    /// A temperature conversion was started.
+    a_temperature_conversion_was_started: bool,
+    /// Calibration offset in °C added to temperature readings.
+    calibration_offset: f32,
+    /// Register the device pointer is known to be set to.
+    pointer: Option<Register>,
+    _mode: PhantomData<MODE>,
+}
+
+impl<I2C, E> Tmp1x2<I2C, mode::Continuous>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Create new instance of the TMP102 or TMP112x device.
+    ///
+    /// By default they are in continuous conversion mode.
+    pub fn new(i2c: I2C, address: SlaveAddr) -> Self {
+        Tmp1x2 {
+            i2c,
+            address: address.addr(DEVICE_BASE_ADDRESS),
+            config: Config::default(),
+            a_temperature_conversion_was_started: false,
+            calibration_offset: 0.0,
+            pointer: None,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Change into one-shot conversion mode (shutdown).
+    ///
+    /// If the mode change failed you will get a `ModeChangeError`.
+    /// You can get the unchanged device back from it.
+    pub fn into_one_shot(mut self) -> Result<Tmp1x2<I2C, mode::OneShot>, ModeChangeError<E, Self>> {
+        if let Err(Error::I2C(e)) = self.apply_config(self.config.with_shutdown(true)) {
+            return Err(ModeChangeError::I2C(e, self));
+        }
+        Ok(Tmp1x2 {
+            i2c: self.i2c,
+            address: self.address,
+            config: self.config,
+            a_temperature_conversion_was_started: false,
+            calibration_offset: self.calibration_offset,
+            pointer: self.pointer,
+            _mode: PhantomData,
+        })
+    }
+
+    /// Read the temperature from the sensor.
+    ///
+    /// The calibration offset set with
+    /// [`set_calibration_offset()`](#method.set_calibration_offset) is added
+    /// to the result.
+    pub fn read_temperature(&mut self) -> Result<f32, Error<E>> {
+        let data = self.read_register(Register::Temperature)?;
+        Ok(convert_temp_from_register(data[0], data[1]) + self.calibration_offset)
+    }
+
+    /// Set the conversion rate.
+    ///
+    /// This is only available in continuous conversion mode.
+    pub fn set_conversion_rate(&mut self, rate: ConversionRate) -> Result<(), Error<E>> {
+        self.apply_config(self.config.with_conversion_rate(rate))
+    }
+
+    /// Run `f` with a temporarily changed conversion rate.
+    ///
+    /// This sets the conversion rate to `rate`, runs `f` and then restores
+    /// the previous conversion rate, also if `f` returned an error. An error
+    /// returned by `f` takes precedence over an error restoring the rate.
+    pub fn with_boosted_rate<T, F>(&mut self, rate: ConversionRate, mut f: F) -> Result<T, Error<E>>
+    where
+        F: FnMut(&mut Self) -> Result<T, Error<E>>,
+    {
+        let previous = self.config.conversion_rate();
+        self.set_conversion_rate(rate)?;
+        let result = f(self);
+        let restored = self.set_conversion_rate(previous);
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+}
+
+impl<I2C, E> Tmp1x2<I2C, mode::OneShot>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Change into continuous conversion mode.
+    ///
+    /// If the mode change failed you will get a `ModeChangeError`.
+    /// You can get the unchanged device back from it.
+    pub fn into_continuous(
+        mut self,
+    ) -> Result<Tmp1x2<I2C, mode::Continuous>, ModeChangeError<E, Self>> {
+        if let Err(Error::I2C(e)) = self.apply_config(self.config.with_shutdown(false)) {
+            return Err(ModeChangeError::I2C(e, self));
+        }
+        Ok(Tmp1x2 {
+            i2c: self.i2c,
+            address: self.address,
+            config: self.config,
+            a_temperature_conversion_was_started: false,
+            calibration_offset: self.calibration_offset,
+            pointer: self.pointer,
+            _mode: PhantomData,
+        })
+    }
+
+    /// Perform a one-shot temperature measurement.
+    ///
+    /// If no temperature conversion was started yet, calling this method
+    /// will start one and return `Error::NotReady`. Subsequent calls check
+    /// the conversion-ready bit and will continue to return
+    /// `Error::NotReady` until the temperature measurement is finished.
+    /// Then it will return the measured temperature in °C, including the
+    /// calibration offset.
+    pub fn read_temperature(&mut self) -> Result<f32, Error<E>> {
+        if !self.a_temperature_conversion_was_started {
+            self.trigger_one_shot_measurement()?;
+            self.a_temperature_conversion_was_started = true;
+            return Err(Error::NotReady);
+        }
+        if !self.is_conversion_ready()? {
+            return Err(Error::NotReady);
+        }
+        let data = self.read_register(Register::Temperature)?;
+        self.a_temperature_conversion_was_started = false;
+        Ok(convert_temp_from_register(data[0], data[1]) + self.calibration_offset)
+    }
+
+    fn trigger_one_shot_measurement(&mut self) -> Result<(), Error<E>> {
+        // This bit is not stored
+        let Config { lsb, msb } = self.config;
+        self.write_register(Register::Config, lsb | BFL::ONE_SHOT, msb)
+    }
+}
+
+impl<I2C, MODE> Tmp1x2<I2C, MODE> {
+    /// Destroy driver instance, return I²C bus instance.
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    /// Set a calibration offset in °C added to every temperature reading.
+    pub fn set_calibration_offset(&mut self, offset_c: f32) {
+        self.calibration_offset = offset_c;
+    }
+
+    /// Get the calibration offset in °C added to every temperature reading.
+    pub fn calibration_offset(&self) -> f32 {
+        self.calibration_offset
+    }
+
+    /// Remove the calibration offset.
+    pub fn clear_calibration_offset(&mut self) {
+        self.calibration_offset = 0.0;
+    }
+}
+
+impl<I2C, E, MODE> Tmp1x2<I2C, MODE>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Read whether an alert is active as defined by the comparator mode.
+    ///
+    /// *NOTE*: This ignores the thermostat mode setting and always corresponds
+    /// to the activation status as defined by the comparator mode.
+    ///
+    /// This method takes into account the alert polarity selected.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn is_comparator_mode_alert_active(&mut self) -> Result<bool, Error<E>> {
+        let data = self.read_register(Register::Config)?;
+        Ok(is_alert_active(data))
+    }
+
+    /// Read whether a conversion result is ready.
+    ///
+    /// This reads the one-shot/conversion-ready bit of the configuration
+    /// register. In one-shot mode, it reports whether the triggered
+    /// measurement is finished.
+    pub fn is_conversion_ready(&mut self) -> Result<bool, Error<E>> {
+        let data = self.read_register(Register::Config)?;
+        Ok((data[1] & BFL::ONE_SHOT) != 0)
+    }
+
+    /// Read the conversion mode the device is currently in.
+    pub fn read_mode(&mut self) -> Result<Mode, Error<E>> {
+        let data = self.read_register(Register::Config)?;
+        if (data[1] & BFL::SHUTDOWN) != 0 {
+            Ok(Mode::OneShot)
+        } else {
+            Ok(Mode::Continuous)
+        }
+    }
+
+    /// Enable the extended measurement mode.
+    ///
+    /// This allows measurement of temperatures above 128°C.
+    pub fn enable_extended_mode(&mut self) -> Result<(), Error<E>> {
+        self.apply_config(self.config.with_extended_mode(true))
+    }
+
+    /// Disable the extended measurement mode.
+    ///
+    /// This puts the device in normal measurement mode. It will not measure
+    /// temperatures above 128°C.
+    pub fn disable_extended_mode(&mut self) -> Result<(), Error<E>> {
+        self.apply_config(self.config.with_extended_mode(false))
+    }
+
+    /// Set the high temperature threshold.
+    ///
+    /// The value provided will be capped to the interval given by
+    /// [`temperature_range()`](#method.temperature_range).
+    pub fn set_high_temperature_threshold(&mut self, temperature: f32) -> Result<(), Error<E>> {
+        self.set_temperature_threshold(temperature, Register::THigh)
+    }
+
+    /// Set the low temperature threshold.
+    ///
+    /// The value provided will be capped to the interval given by
+    /// [`temperature_range()`](#method.temperature_range).
+    pub fn set_low_temperature_threshold(&mut self, temperature: f32) -> Result<(), Error<E>> {
+        self.set_temperature_threshold(temperature, Register::TLow)
+    }
+
+    /// Set the fault queue.
+    ///
+    /// Set the number of consecutive faults that will trigger an alert.
+    pub fn set_fault_queue(&mut self, fq: FaultQueue) -> Result<(), Error<E>> {
+        self.apply_config(self.config.with_fault_queue(fq))
+    }
+
+    /// Set the alert polarity.
+    pub fn set_alert_polarity(&mut self, polarity: AlertPolarity) -> Result<(), Error<E>> {
+        self.apply_config(self.config.with_alert_polarity(polarity))
+    }
+
+    /// Set the thermostat mode.
+    pub fn set_thermostat_mode(&mut self, mode: ThermostatMode) -> Result<(), Error<E>> {
+        self.apply_config(self.config.with_thermostat_mode(mode))
+    }
+
+    /// Get the temperature range `(min, max)` in °C of the measurement mode
+    /// currently in effect.
+    pub fn temperature_range(&self) -> (f32, f32) {
+        if self.config.is_extended_mode() {
+            (EXTENDED_MIN_C, EXTENDED_MAX_C)
+        } else {
+            (NORMAL_MIN_C, NORMAL_MAX_C)
+        }
+    }
+
+    /// Reset the internal state of this driver to the default values.
+    ///
+    /// *Note:* This does not alter the state or configuration of the device.
+    ///
+    /// This resets the cached configuration register value in this driver to
+    /// the power-up (reset) configuration of the device. It also forgets
+    /// the register the device pointer was last set to.
+    pub fn reset_internal_driver_state(&mut self) {
+        self.config = Config::default();
+        self.pointer = None;
+    }
+
+    /// Probe whether the device behaves like a TMP102 or TMP112.
+    ///
+    /// This reads the configuration register and checks that the read-only
+    /// resolution bits are set. Then the extended mode bit is toggled and
+    /// read back to check that it is writable. Finally, the original
+    /// configuration is written back.
+    ///
+    /// A different device at the same address would most likely fail one of
+    /// these checks. The TMP102 and TMP112 cannot be told apart.
+    pub fn probe(&mut self) -> Result<DeviceKind, Error<E>> {
+        let [msb, lsb] = self.read_register(Register::Config)?;
+        if !fixed_bits_match([msb, lsb]) {
+            return Ok(DeviceKind::Unknown);
+        }
+        // writing the one-shot bit would trigger a conversion in shutdown mode
+        let lsb = lsb & !BFL::ONE_SHOT;
+        let toggled = msb ^ BFH::EXTENDED_MODE;
+        self.write_register(Register::Config, lsb, toggled)?;
+        let [read_msb, _] = self.read_register(Register::Config)?;
+        self.write_register(Register::Config, lsb, msb)?;
+        if (read_msb & BFH::EXTENDED_MODE) == (toggled & BFH::EXTENDED_MODE) {
+            Ok(DeviceKind::Tmp1x2)
+        } else {
+            Ok(DeviceKind::Unknown)
+        }
+    }
+
+    fn set_temperature_threshold(
+        &mut self,
+        temperature: f32,
+        register: Register,
+    ) -> Result<(), Error<E>> {
+        let (msb, lsb) = self.config.threshold_bytes(temperature);
+        self.write_register(register, lsb, msb)
+    }
+
+    fn apply_config(&mut self, config: Config) -> Result<(), Error<E>> {
+        self.write_register(Register::Config, config.lsb, config.msb)?;
+        self.config = config;
+        Ok(())
+    }
+
+    fn write_register(&mut self, register: Register, lsb: u8, msb: u8) -> Result<(), Error<E>> {
+        let payload = register_write(register, lsb, msb);
+        trace!("write to {:?}: {:?}", self.address, payload);
+        self.pointer = None;
+        self.i2c.write(self.address, &payload).map_err(Error::I2C)?;
+        self.pointer = Some(register);
+        Ok(())
+    }
+
+    fn read_register(&mut self, register: Register) -> Result<[u8; 2], Error<E>> {
+        let pointer = self.pointer.take();
+        let data = if needs_pointer(pointer, register) {
+            let mut data = [0; REGISTER_READ_LEN];
+            self.i2c
+                .write_read(self.address, &[register as u8], &mut data)
+                .map_err(Error::I2C)?;
+            trace!(
+                "write_read to {:?}: {:?} -> {:?}",
+                self.address,
+                register as u8,
+                data
+            );
+            register_data(self.address, register, data)?
+        } else {
+            let mut data = [0; 2];
+            self.i2c.read(self.address, &mut data).map_err(Error::I2C)?;
+            trace!("read from {:?}: {:?}", self.address, data);
+            data
+        };
+        self.pointer = Some(register);
+        Ok(data)
+    }
+}
@@ -0,0 +1,42 @@
+//! Bus-independent encoding of the register accesses.
+//!
+//! This is shared by the async and the blocking driver so that only the
+//! transport differs between them.
+
+use crate::{Error, Register};
+
+/// Number of bytes read from a register after sending the register pointer.
+///
+/// With the `pec` feature, this includes the packet error code byte.
+pub(crate) const REGISTER_READ_LEN: usize = if cfg!(feature = "pec") { 3 } else { 2 };
+
+/// Bytes sent to write a register, most significant data byte first.
+pub(crate) fn register_write(register: Register, lsb: u8, msb: u8) -> [u8; 3] {
+    [register as u8, msb, lsb]
+}
+
+/// Whether the register pointer must be sent before reading `register`.
+///
+/// The device keeps the register pointer after each access, so it is only
+/// sent if the pointer is not known to be set to `register` already. With
+/// the `pec` feature it is always sent, since the packet error code covers
+/// the whole transaction including the pointer.
+pub(crate) fn needs_pointer(pointer: Option<Register>, register: Register) -> bool {
+    cfg!(feature = "pec") || pointer != Some(register)
+}
+
+/// Get the register data read after sending the register pointer.
+///
+/// With the `pec` feature, the packet error code is checked.
+#[cfg_attr(not(feature = "pec"), allow(unused_variables))]
+pub(crate) fn register_data<E>(
+    address: u8,
+    register: Register,
+    data: [u8; REGISTER_READ_LEN],
+) -> Result<[u8; 2], Error<E>> {
+    #[cfg(feature = "pec")]
+    if data[2] != crate::pec::read_pec(address, register as u8, [data[0], data[1]]) {
+        return Err(Error::Pec);
+    }
+    Ok([data[0], data[1]])
+}
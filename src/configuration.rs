@@ -1,22 +1,34 @@
-use crate::conversion::{convert_temp_to_register_extended, convert_temp_to_register_normal};
+use crate::bus::register_write;
+use crate::conversion::{
+    convert_raw_to_register_extended, convert_raw_to_register_normal,
+    convert_temp_to_register_extended, convert_temp_to_register_normal, decode_temperature,
+};
+use crate::reading::fixed_bits_match;
 use crate::{
-    marker::mode, AlertPolarity, BitFlagsHigh as BFH, BitFlagsLow as BFL, Config,
-    ConversionRate as CR, Error, FaultQueue, ModeChangeError, Register, ThermostatMode, Tmp1x2,
+    marker::mode, recommend_fault_queue, requires_shutdown, transfer_size, AlertPolarity,
+    BitFlagsHigh as BFH, BitFlagsLow as BFL, Config, ConfigChange, ConfigField,
+    ConversionRate as CR, DesiredConfig, DeviceKind, DeviceState, Error, ExtendedTemp, FaultQueue,
+    ModeChangeError, NormalTemp, Operation, Register, ThermostatMode, Tmp1x2,
+    DEFAULT_THRESHOLDS_RAW, EXTENDED_MAX_C, EXTENDED_MIN_C, NORMAL_MAX_C, NORMAL_MIN_C,
 };
 use core::marker::PhantomData;
 use embedded_hal_async::i2c;
 
-impl<I2C, E> Tmp1x2<I2C, mode::Continuous>
+impl<I2C, E, UNIT> Tmp1x2<I2C, mode::Continuous, UNIT>
 where
     I2C: i2c::I2c<Error = E>,
 {
     /// Change into one-shot conversion mode (shutdown).
     ///
+    /// The device shuts down after completing the conversion in progress, if
+    /// any, so the last temperature sample is not truncated and no waiting
+    /// is necessary before calling this.
+    ///
     /// If the mode change failed you will get a `ModeChangeError`.
     /// You can get the unchanged device back from it.
     pub async fn into_one_shot(
         mut self,
-    ) -> Result<Tmp1x2<I2C, mode::OneShot>, ModeChangeError<E, Self>> {
+    ) -> Result<Tmp1x2<I2C, mode::OneShot, UNIT>, ModeChangeError<E, Self>> {
         if let Err(Error::I2C(e)) = self.config_one_shot().await {
             return Err(ModeChangeError::I2C(e, self));
         }
@@ -25,12 +37,40 @@ where
             address: self.address,
             config: self.config,
             a_temperature_conversion_was_started: false,
+            sample_count: self.sample_count,
+            calibration_offset: self.calibration_offset,
+            config_dirty: self.config_dirty,
+            defer_config_writes: self.defer_config_writes,
+            safe_config_changes: self.safe_config_changes,
+            last_temperature: self.last_temperature,
+            pointer: self.pointer,
+            thresholds_raw: self.thresholds_raw,
+            alert_enabled: self.alert_enabled,
             _mode: PhantomData,
         })
     }
+
+    /// Set the conversion rate.
+    ///
+    /// The conversion rate only applies to continuous conversion mode, so
+    /// this is not available in one-shot mode. Use
+    /// [`set_conversion_rate_continuous()`](#method.set_conversion_rate_continuous)
+    /// to set it while changing into continuous conversion mode instead.
+    ///
+    /// ```compile_fail
+    /// # async fn f<I2C: embedded_hal_async::i2c::I2c>(
+    /// #     mut dev: tmp1x2::Tmp1x2<I2C, tmp1x2::marker::mode::OneShot>,
+    /// # ) {
+    /// dev.set_conversion_rate(tmp1x2::ConversionRate::_8Hz).await.ok();
+    /// # }
+    /// ```
+    pub async fn set_conversion_rate(&mut self, rate: CR) -> Result<(), Error<E>> {
+        self.apply_config(self.config.with_conversion_rate(rate))
+            .await
+    }
 }
 
-impl<I2C, E> Tmp1x2<I2C, mode::OneShot>
+impl<I2C, E, UNIT> Tmp1x2<I2C, mode::OneShot, UNIT>
 where
     I2C: i2c::I2c<Error = E>,
 {
@@ -40,7 +80,7 @@ where
     /// You can get the unchanged device back from it.
     pub async fn into_continuous(
         mut self,
-    ) -> Result<Tmp1x2<I2C, mode::Continuous>, ModeChangeError<E, Self>> {
+    ) -> Result<Tmp1x2<I2C, mode::Continuous, UNIT>, ModeChangeError<E, Self>> {
         if let Err(Error::I2C(e)) = self.config_continuous().await {
             return Err(ModeChangeError::I2C(e, self));
         }
@@ -49,46 +89,249 @@ where
             address: self.address,
             config: self.config,
             a_temperature_conversion_was_started: false,
+            sample_count: self.sample_count,
+            calibration_offset: self.calibration_offset,
+            config_dirty: self.config_dirty,
+            defer_config_writes: self.defer_config_writes,
+            safe_config_changes: self.safe_config_changes,
+            last_temperature: self.last_temperature,
+            pointer: self.pointer,
+            thresholds_raw: self.thresholds_raw,
+            alert_enabled: self.alert_enabled,
+            _mode: PhantomData,
+        })
+    }
+
+    /// Set the conversion rate and change into continuous conversion mode.
+    ///
+    /// This clears the shutdown bit and sets the conversion rate in a single
+    /// write so that the conversion rate is guaranteed to take effect.
+    ///
+    /// If the mode change failed you will get a `ModeChangeError`.
+    /// You can get the unchanged device back from it.
+    pub async fn set_conversion_rate_continuous(
+        mut self,
+        rate: CR,
+    ) -> Result<Tmp1x2<I2C, mode::Continuous, UNIT>, ModeChangeError<E, Self>> {
+        let config = self.config.with_shutdown(false).with_conversion_rate(rate);
+        let result = self.apply_config(config).await;
+        if let Err(Error::I2C(e)) = result {
+            return Err(ModeChangeError::I2C(e, self));
+        }
+        Ok(Tmp1x2 {
+            i2c: self.i2c,
+            address: self.address,
+            config: self.config,
+            a_temperature_conversion_was_started: false,
+            sample_count: self.sample_count,
+            calibration_offset: self.calibration_offset,
+            config_dirty: self.config_dirty,
+            defer_config_writes: self.defer_config_writes,
+            safe_config_changes: self.safe_config_changes,
+            last_temperature: self.last_temperature,
+            pointer: self.pointer,
+            thresholds_raw: self.thresholds_raw,
+            alert_enabled: self.alert_enabled,
             _mode: PhantomData,
         })
     }
 
     pub(crate) async fn trigger_one_shot_measurement(&mut self) -> Result<(), Error<E>> {
         // This bit is not stored
-        self.i2c
-            .write(
-                self.address,
-                &[
-                    Register::CONFIG,
-                    self.config.msb,
-                    self.config.lsb | BFL::ONE_SHOT,
-                ],
-            )
-            .await
-            .map_err(Error::I2C)
+        let Config { lsb, msb } = self.config;
+        self.send_register_bytes(Register::Config, lsb | BFL::ONE_SHOT, msb)
+            .await?;
+        // the cached configuration was written along
+        self.config_dirty = false;
+        Ok(())
     }
 }
 
-impl<I2C, E, MODE> Tmp1x2<I2C, MODE>
+impl<I2C, E, MODE, UNIT> Tmp1x2<I2C, MODE, UNIT>
 where
     I2C: i2c::I2c<Error = E>,
 {
     async fn config_continuous(&mut self) -> Result<(), Error<E>> {
-        let Config { lsb, msb } = self.config;
-        self.write_config(lsb & !BFL::SHUTDOWN, msb).await
+        self.apply_config(self.config.with_shutdown(false)).await
     }
 
     async fn config_one_shot(&mut self) -> Result<(), Error<E>> {
-        let Config { lsb, msb } = self.config;
-        self.write_config(lsb | BFL::SHUTDOWN, msb).await
+        self.apply_config(self.config.with_shutdown(true)).await
+    }
+
+    /// Apply a configuration to the device.
+    ///
+    /// The configuration register is only written if `target` differs from
+    /// the configuration last written through this driver. Otherwise this
+    /// returns without any bus traffic, which saves power when settings are
+    /// reapplied idempotently.
+    ///
+    /// The conversion mode of `target` is ignored and the current one kept.
+    /// Use [`into_continuous()`](#method.into_continuous) and
+    /// [`into_one_shot()`](#method.into_one_shot) to change it. The read-only
+    /// alert (`AL`) and resolution (`R1`/`R0`) bits of `target` are ignored
+    /// as well, so a configuration read back from the device compares equal.
+    ///
+    /// The write is deferred and bracketed by a shutdown as for the other
+    /// setters, see
+    /// [`set_deferred_config_writes()`](#method.set_deferred_config_writes)
+    /// and [`set_safe_config_changes()`](#method.set_safe_config_changes).
+    pub async fn reconfigure(&mut self, target: Config) -> Result<(), Error<E>> {
+        let config = self.config.with_settings_of(target);
+        if config == self.config {
+            return Ok(());
+        }
+        self.apply_config(config).await
+    }
+
+    /// Copy the configuration of another driver instance to this device.
+    ///
+    /// The configuration cached by `other` is written to the configuration
+    /// register of this device, so that several sensors can be configured
+    /// uniformly. The register is always written, even if the cached
+    /// configuration of this driver already matches or configuration writes
+    /// are deferred. Safe configuration changes are bracketed with a
+    /// shutdown as for the other setters, see
+    /// [`set_safe_config_changes()`](#method.set_safe_config_changes).
+    ///
+    /// The conversion mode of `other` is ignored and the current one kept.
+    /// The temperature thresholds are not copied, see
+    /// [`save_state()`](#method.save_state) and
+    /// [`restore_state()`](#method.restore_state) for that.
+    pub async fn copy_config_from<I2C2, MODE2, UNIT2>(
+        &mut self,
+        other: &Tmp1x2<I2C2, MODE2, UNIT2>,
+    ) -> Result<(), Error<E>> {
+        let shutdown = self.config.lsb & BFL::SHUTDOWN;
+        let lsb = (other.config.lsb & !BFL::SHUTDOWN & !BFL::ONE_SHOT) | shutdown;
+        self.force_config(Config {
+            lsb,
+            msb: other.config.msb,
+        })
+        .await
+    }
+
+    /// Apply a complete configuration including the temperature thresholds.
+    ///
+    /// The thresholds are validated against the temperature range of the
+    /// desired measurement mode before anything is written. If they do not
+    /// fit, `Error::InvalidInputData` is returned. Otherwise the
+    /// configuration register is written, followed by the low and high
+    /// temperature threshold registers. The conversion mode is not changed.
+    pub async fn apply(&mut self, desired: DesiredConfig) -> Result<(), Error<E>> {
+        let (min, max) = if desired.extended_mode {
+            (EXTENDED_MIN_C, EXTENDED_MAX_C)
+        } else {
+            (NORMAL_MIN_C, NORMAL_MAX_C)
+        };
+        let range = min..=max;
+        if !range.contains(&desired.low_threshold) || !range.contains(&desired.high_threshold) {
+            return Err(Error::InvalidInputData);
+        }
+        let config = self
+            .config
+            .with_conversion_rate(desired.conversion_rate)
+            .with_fault_queue(desired.fault_queue)
+            .with_alert_polarity(desired.alert_polarity)
+            .with_thermostat_mode(desired.thermostat_mode)
+            .with_extended_mode(desired.extended_mode);
+        self.apply_config(config).await?;
+        self.set_temperature_threshold(desired.low_threshold, Register::TLow)
+            .await?;
+        self.set_temperature_threshold(desired.high_threshold, Register::THigh)
+            .await
+    }
+
+    /// Get the configuration resulting from a change without applying it.
+    ///
+    /// The change is applied to the cached configuration as the
+    /// corresponding setter would do. This does not access the device and
+    /// allows showing what a change would do before making it.
+    pub fn preview_config_after(&self, change: ConfigChange) -> Config {
+        self.config.with_change(change)
+    }
+
+    /// Save the configuration and temperature thresholds.
+    ///
+    /// This does not access the device. The state is based on the values
+    /// written through this driver, or the power-up values if none were
+    /// written. It can be used to reprogram the device identically with
+    /// [`restore_state()`](#method.restore_state), for example after a
+    /// power cycle.
+    pub fn save_state(&self) -> DeviceState {
+        DeviceState {
+            config: self.config,
+            t_low_raw: self.thresholds_raw.0,
+            t_high_raw: self.thresholds_raw.1,
+        }
+    }
+
+    /// Restore the configuration and temperature thresholds.
+    ///
+    /// This always writes the configuration register, followed by the low
+    /// and high temperature threshold registers, even if the state matches
+    /// the one cached by this driver, since the device may have lost it.
+    /// The conversion mode is not changed. The configuration write is
+    /// deferred and bracketed by a shutdown as for the other setters.
+    pub async fn restore_state(&mut self, state: DeviceState) -> Result<(), Error<E>> {
+        let shutdown = self.config.lsb & BFL::SHUTDOWN;
+        let lsb = (state.config.lsb & !BFL::SHUTDOWN & !BFL::ONE_SHOT) | shutdown;
+        self.apply_config(Config {
+            lsb,
+            msb: state.config.msb,
+        })
+        .await?;
+        let [msb, lsb] = state.t_low_raw.to_be_bytes();
+        self.write_register_bytes(Register::TLow, lsb, msb).await?;
+        let [msb, lsb] = state.t_high_raw.to_be_bytes();
+        self.write_register_bytes(Register::THigh, lsb, msb).await
+    }
+
+    /// Apply a raw configuration word to the device.
+    ///
+    /// This is like [`reconfigure()`](#method.reconfigure) for a word in the
+    /// layout of [`Config::bits()`](struct.Config.html#method.bits), for
+    /// example parsed from a register dump with
+    /// [`Config::parse_hex()`](struct.Config.html#method.parse_hex).
+    pub async fn apply_config_word(&mut self, word: u16) -> Result<(), Error<E>> {
+        self.reconfigure(Config::from_bits(word)).await
+    }
+
+    /// Probe whether the device behaves like a TMP102 or TMP112.
+    ///
+    /// This reads the configuration register and checks that the read-only
+    /// resolution bits are set. Then the extended mode bit is toggled and
+    /// read back to check that it is writable. Finally, the original
+    /// configuration is written back.
+    ///
+    /// A different device at the same address would most likely fail one of
+    /// these checks. The TMP102 and TMP112 cannot be told apart.
+    pub async fn probe(&mut self) -> Result<DeviceKind, Error<E>> {
+        let [msb, lsb] = self.read_register(Register::Config).await?;
+        if !fixed_bits_match([msb, lsb]) {
+            return Ok(DeviceKind::Unknown);
+        }
+        // writing the one-shot bit would trigger a conversion in shutdown mode
+        let lsb = lsb & !BFL::ONE_SHOT;
+        let toggled = msb ^ BFH::EXTENDED_MODE;
+        self.write_register_bytes(Register::Config, lsb, toggled)
+            .await?;
+        let [read_msb, _] = self.read_register(Register::Config).await?;
+        self.write_register_bytes(Register::Config, lsb, msb)
+            .await?;
+        if (read_msb & BFH::EXTENDED_MODE) == (toggled & BFH::EXTENDED_MODE) {
+            Ok(DeviceKind::Tmp1x2)
+        } else {
+            Ok(DeviceKind::Unknown)
+        }
     }
 
     /// Enable the extended measurement mode.
     ///
     /// This allows measurement of temperatures above 128°C.
     pub async fn enable_extended_mode(&mut self) -> Result<(), Error<E>> {
-        let Config { lsb, msb } = self.config;
-        self.write_config(lsb, msb | BFH::EXTENDED_MODE).await
+        self.apply_config(self.config.with_extended_mode(true))
+            .await
     }
 
     /// Disable the extended measurement mode.
@@ -96,116 +339,394 @@ where
     /// This puts the device in normal measurement mode. It will not measure
     /// temperatures above 128°C.
     pub async fn disable_extended_mode(&mut self) -> Result<(), Error<E>> {
-        let Config { lsb, msb } = self.config;
-        self.write_config(lsb, msb & !BFH::EXTENDED_MODE).await
-    }
-
-    /// Set the conversion rate when in continuous conversion mode.
-    pub async fn set_conversion_rate(&mut self, rate: CR) -> Result<(), Error<E>> {
-        let Config { lsb, msb } = self.config;
-        match rate {
-            CR::_0_25Hz => {
-                self.write_config(lsb, msb & !BFH::CONV_RATE1 & !BFH::CONV_RATE0)
-                    .await
-            }
-            CR::_1Hz => {
-                self.write_config(lsb, msb & !BFH::CONV_RATE1 | BFH::CONV_RATE0)
-                    .await
-            }
-            CR::_4Hz => {
-                self.write_config(lsb, msb | BFH::CONV_RATE1 & !BFH::CONV_RATE0)
-                    .await
-            }
-            CR::_8Hz => {
-                self.write_config(lsb, msb | BFH::CONV_RATE1 | BFH::CONV_RATE0)
-                    .await
-            }
-        }
+        self.apply_config(self.config.with_extended_mode(false))
+            .await
     }
 
     /// Set the high temperature threshold.
     ///
     /// The value provided will be capped to be in the interval
-    /// `[-128.0, 127.9375]` in normal mode and `[-256.0, 255.875]` in
-    /// extended mode.
+    /// `[NORMAL_MIN_C, NORMAL_MAX_C]` (`[-128.0, 127.9375]`) in normal mode and
+    /// `[EXTENDED_MIN_C, EXTENDED_MAX_C]` (`[-256.0, 255.9375]`) in extended
+    /// mode. See also [`temperature_range()`](#method.temperature_range).
+    /// It is then rounded to the nearest multiple of 0.0625°C, with ties
+    /// rounded to even.
     pub async fn set_high_temperature_threshold(
         &mut self,
         temperature: f32,
     ) -> Result<(), Error<E>> {
-        self.set_temperature_threshold(temperature, Register::T_HIGH)
+        self.set_temperature_threshold(temperature, Register::THigh)
             .await
     }
 
     /// Set the low temperature threshold.
     ///
     /// The value provided will be capped to be in the interval
-    /// `[-128.0, 127.9375]` in normal mode and `[-256.0, 255.875]` in
-    /// extended mode.
+    /// `[NORMAL_MIN_C, NORMAL_MAX_C]` (`[-128.0, 127.9375]`) in normal mode and
+    /// `[EXTENDED_MIN_C, EXTENDED_MAX_C]` (`[-256.0, 255.9375]`) in extended
+    /// mode. See also [`temperature_range()`](#method.temperature_range).
+    /// It is then rounded to the nearest multiple of 0.0625°C, with ties
+    /// rounded to even.
     pub async fn set_low_temperature_threshold(
         &mut self,
         temperature: f32,
     ) -> Result<(), Error<E>> {
-        self.set_temperature_threshold(temperature, Register::T_LOW)
+        self.set_temperature_threshold(temperature, Register::TLow)
+            .await
+    }
+
+    /// Set the high temperature threshold to a range-checked value.
+    ///
+    /// The value fits in both measurement modes. It is rounded as in
+    /// [`set_high_temperature_threshold()`](#method.set_high_temperature_threshold).
+    pub async fn set_high_temperature_threshold_normal(
+        &mut self,
+        temperature: NormalTemp,
+    ) -> Result<(), Error<E>> {
+        self.set_temperature_threshold(temperature.celsius(), Register::THigh)
+            .await
+    }
+
+    /// Set the low temperature threshold to a range-checked value.
+    ///
+    /// The value fits in both measurement modes. It is rounded as in
+    /// [`set_low_temperature_threshold()`](#method.set_low_temperature_threshold).
+    pub async fn set_low_temperature_threshold_normal(
+        &mut self,
+        temperature: NormalTemp,
+    ) -> Result<(), Error<E>> {
+        self.set_temperature_threshold(temperature.celsius(), Register::TLow)
+            .await
+    }
+
+    /// Set the high temperature threshold to a range-checked value for the
+    /// extended measurement mode.
+    ///
+    /// Returns `Error::InvalidInputData` without writing anything if the
+    /// extended measurement mode is not enabled. The value is rounded as in
+    /// [`set_high_temperature_threshold()`](#method.set_high_temperature_threshold).
+    pub async fn set_high_temperature_threshold_extended(
+        &mut self,
+        temperature: ExtendedTemp,
+    ) -> Result<(), Error<E>> {
+        if !self.config.is_extended_mode() {
+            return Err(Error::InvalidInputData);
+        }
+        self.set_temperature_threshold(temperature.celsius(), Register::THigh)
+            .await
+    }
+
+    /// Set the low temperature threshold to a range-checked value for the
+    /// extended measurement mode.
+    ///
+    /// Returns `Error::InvalidInputData` without writing anything if the
+    /// extended measurement mode is not enabled. The value is rounded as in
+    /// [`set_low_temperature_threshold()`](#method.set_low_temperature_threshold).
+    pub async fn set_low_temperature_threshold_extended(
+        &mut self,
+        temperature: ExtendedTemp,
+    ) -> Result<(), Error<E>> {
+        if !self.config.is_extended_mode() {
+            return Err(Error::InvalidInputData);
+        }
+        self.set_temperature_threshold(temperature.celsius(), Register::TLow)
+            .await
+    }
+
+    /// Set the thresholds to a band around a setpoint in °F.
+    ///
+    /// The low threshold is set to `setpoint_f - hysteresis_f` and the high
+    /// threshold to `setpoint_f + hysteresis_f`, converted to °C. Both are
+    /// validated against the temperature range of the current measurement
+    /// mode before anything is written. If they do not fit or `hysteresis_f`
+    /// is negative, `Error::InvalidInputData` is returned. Otherwise the low
+    /// and high temperature threshold registers are written in this order.
+    pub async fn set_thermostat_setpoint_fahrenheit(
+        &mut self,
+        setpoint_f: f32,
+        hysteresis_f: f32,
+    ) -> Result<(), Error<E>> {
+        if hysteresis_f < 0.0 {
+            return Err(Error::InvalidInputData);
+        }
+        let low = fahrenheit_to_celsius(setpoint_f - hysteresis_f);
+        let high = fahrenheit_to_celsius(setpoint_f + hysteresis_f);
+        let (min, max) = self.temperature_range();
+        let range = min..=max;
+        if !range.contains(&low) || !range.contains(&high) {
+            return Err(Error::InvalidInputData);
+        }
+        self.set_temperature_threshold(low, Register::TLow).await?;
+        self.set_temperature_threshold(high, Register::THigh).await
+    }
+
+    /// Set the high temperature threshold and report whether it was clamped.
+    ///
+    /// Like [`set_high_temperature_threshold()`](#method.set_high_temperature_threshold)
+    /// the value is always written, capped to the interval allowed by the
+    /// current measurement mode. Returns `true` if the value had to be capped.
+    pub async fn set_high_temperature_threshold_saturating(
+        &mut self,
+        temperature: f32,
+    ) -> Result<bool, Error<E>> {
+        self.set_temperature_threshold_saturating(temperature, Register::THigh)
+            .await
+    }
+
+    /// Set the low temperature threshold and report whether it was clamped.
+    ///
+    /// Like [`set_low_temperature_threshold()`](#method.set_low_temperature_threshold)
+    /// the value is always written, capped to the interval allowed by the
+    /// current measurement mode. Returns `true` if the value had to be capped.
+    pub async fn set_low_temperature_threshold_saturating(
+        &mut self,
+        temperature: f32,
+    ) -> Result<bool, Error<E>> {
+        self.set_temperature_threshold_saturating(temperature, Register::TLow)
+            .await
+    }
+
+    /// Set the high temperature threshold in units of 0.0625°C.
+    ///
+    /// The value is aligned to the 12-bit register format in normal mode and
+    /// to the 13-bit format in extended mode, and capped to the range of the
+    /// format.
+    pub async fn set_high_threshold_raw(&mut self, counts: i16) -> Result<(), Error<E>> {
+        let (msb, lsb) = self.config.threshold_bytes_raw(counts);
+        self.write_register_bytes(Register::THigh, lsb, msb).await
+    }
+
+    /// Set the low temperature threshold in units of 0.0625°C.
+    ///
+    /// The value is aligned to the 12-bit register format in normal mode and
+    /// to the 13-bit format in extended mode, and capped to the range of the
+    /// format.
+    pub async fn set_low_threshold_raw(&mut self, counts: i16) -> Result<(), Error<E>> {
+        let (msb, lsb) = self.config.threshold_bytes_raw(counts);
+        self.write_register_bytes(Register::TLow, lsb, msb).await
+    }
+
+    /// Set the high temperature threshold and return the value programmed.
+    ///
+    /// Like [`set_high_temperature_threshold()`](#method.set_high_temperature_threshold)
+    /// the value is capped to the interval allowed by the current measurement
+    /// mode. It is then rounded to the resolution of the device. Returns the
+    /// temperature in °C that the device actually uses as threshold.
+    pub async fn set_high_temperature_threshold_actual(
+        &mut self,
+        temperature: f32,
+    ) -> Result<f32, Error<E>> {
+        self.set_temperature_threshold_actual(temperature, Register::THigh)
+            .await
+    }
+
+    /// Set the low temperature threshold and return the value programmed.
+    ///
+    /// Like [`set_low_temperature_threshold()`](#method.set_low_temperature_threshold)
+    /// the value is capped to the interval allowed by the current measurement
+    /// mode. It is then rounded to the resolution of the device. Returns the
+    /// temperature in °C that the device actually uses as threshold.
+    pub async fn set_low_temperature_threshold_actual(
+        &mut self,
+        temperature: f32,
+    ) -> Result<f32, Error<E>> {
+        self.set_temperature_threshold_actual(temperature, Register::TLow)
             .await
     }
 
+    async fn set_temperature_threshold_actual(
+        &mut self,
+        temperature: f32,
+        register: Register,
+    ) -> Result<f32, Error<E>> {
+        let (msb, lsb) = self.config.threshold_bytes(temperature);
+        self.write_register_bytes(register, lsb, msb).await?;
+        Ok(decode_temperature(msb, lsb, self.config.is_extended_mode()))
+    }
+
+    async fn set_temperature_threshold_saturating(
+        &mut self,
+        temperature: f32,
+        register: Register,
+    ) -> Result<bool, Error<E>> {
+        let (min, max) = self.temperature_range();
+        let was_clamped = !(min..=max).contains(&temperature);
+        self.set_temperature_threshold(temperature, register)
+            .await?;
+        Ok(was_clamped)
+    }
+
     async fn set_temperature_threshold(
         &mut self,
         temperature: f32,
-        register: u8,
+        register: Register,
     ) -> Result<(), Error<E>> {
-        if (self.config.msb & BFH::EXTENDED_MODE) != 0 {
-            let (msb, lsb) = convert_temp_to_register_extended(temperature);
-            self.write_register(register, lsb, msb).await
-        } else {
-            let (msb, lsb) = convert_temp_to_register_normal(temperature);
-            self.write_register(register, lsb, msb).await
-        }
+        let (msb, lsb) = self.config.threshold_bytes(temperature);
+        self.write_register_bytes(register, lsb, msb).await
     }
 
     /// Set the fault queue.
     ///
     /// Set the number of consecutive faults that will trigger an alert.
     pub async fn set_fault_queue(&mut self, fq: FaultQueue) -> Result<(), Error<E>> {
-        let Config { lsb, msb } = self.config;
-        match fq {
-            FaultQueue::_1 => {
-                self.write_config(lsb & !BFL::FAULT_QUEUE1 & !BFL::FAULT_QUEUE0, msb)
-                    .await
-            }
-            FaultQueue::_2 => {
-                self.write_config(lsb & !BFL::FAULT_QUEUE1 | BFL::FAULT_QUEUE0, msb)
-                    .await
-            }
-            FaultQueue::_4 => {
-                self.write_config(lsb | BFL::FAULT_QUEUE1 & !BFL::FAULT_QUEUE0, msb)
-                    .await
-            }
-            FaultQueue::_6 => {
-                self.write_config(lsb | BFL::FAULT_QUEUE1 | BFL::FAULT_QUEUE0, msb)
-                    .await
-            }
-        }
+        self.apply_config(self.config.with_fault_queue(fq)).await
+    }
+
+    /// Set the smallest fault queue filtering out spurious faults.
+    ///
+    /// See [`recommend_fault_queue()`](fn.recommend_fault_queue.html).
+    pub async fn set_fault_queue_for_noise(
+        &mut self,
+        expected_noise_samples: u8,
+    ) -> Result<(), Error<E>> {
+        self.set_fault_queue(recommend_fault_queue(expected_noise_samples))
+            .await
     }
 
     /// Set the alert polarity.
     pub async fn set_alert_polarity(&mut self, polarity: AlertPolarity) -> Result<(), Error<E>> {
-        let Config { lsb, msb } = self.config;
-        match polarity {
-            AlertPolarity::ActiveLow => self.write_config(lsb & !BFL::ALERT_POLARITY, msb).await,
-            AlertPolarity::ActiveHigh => self.write_config(lsb | BFL::ALERT_POLARITY, msb).await,
-        }
+        self.apply_config(self.config.with_alert_polarity(polarity))
+            .await
     }
 
     /// Set the thermostat mode.
     pub async fn set_thermostat_mode(&mut self, mode: ThermostatMode) -> Result<(), Error<E>> {
-        let Config { lsb, msb } = self.config;
-        match mode {
-            ThermostatMode::Comparator => self.write_config(lsb & !BFL::THERMOSTAT, msb).await,
-            ThermostatMode::Interrupt => self.write_config(lsb | BFL::THERMOSTAT, msb).await,
+        self.apply_config(self.config.with_thermostat_mode(mode))
+            .await
+    }
+
+    /// Get the time in milliseconds until a new temperature sample is available.
+    ///
+    /// This is calculated from the conversion rate set through this driver
+    /// and the time of the last read and current time as provided by the
+    /// caller, so that the driver does not need to own a clock.
+    /// Returns 0 if a new sample is already available.
+    pub fn next_sample_due(&self, last_read_ms: u64, now_ms: u64) -> u64 {
+        let elapsed = now_ms.saturating_sub(last_read_ms);
+        let period = self.config.conversion_rate().period_ms();
+        period.saturating_sub(elapsed)
+    }
+
+    /// Estimate the I²C bus traffic in bytes per second of reading every
+    /// temperature sample.
+    ///
+    /// This assumes one temperature read per conversion at the conversion
+    /// rate set through this driver, with the register pointer already set
+    /// to the temperature register. The bytes of each read are counted as
+    /// in [`transfer_size()`](fn.transfer_size.html) for
+    /// `Operation::ReadCurrentRegister`. The result is rounded up.
+    pub fn bus_bytes_per_second(&self) -> u32 {
+        let period = self.config.conversion_rate().period_ms();
+        let bytes = transfer_size(Operation::ReadCurrentRegister) as u64;
+        ((bytes * 1000 + period - 1) / period) as u32
+    }
+
+    /// Get the temperature range `(min, max)` in °C of the measurement mode
+    /// currently in effect.
+    ///
+    /// This is `(NORMAL_MIN_C, NORMAL_MAX_C)` in normal mode and
+    /// `(EXTENDED_MIN_C, EXTENDED_MAX_C)` in extended mode. Temperature
+    /// thresholds are capped to this range.
+    pub fn temperature_range(&self) -> (f32, f32) {
+        if self.config.is_extended_mode() {
+            (EXTENDED_MIN_C, EXTENDED_MAX_C)
+        } else {
+            (NORMAL_MIN_C, NORMAL_MAX_C)
         }
     }
 
+    /// Get the resolution of the temperature register currently in effect.
+    ///
+    /// Returns 13 bits in extended measurement mode and 12 bits in normal mode.
+    pub fn temperature_resolution_bits(&self) -> u8 {
+        if self.config.is_extended_mode() {
+            13
+        } else {
+            12
+        }
+    }
+
+    /// Get the temperature corresponding to one LSB of the temperature
+    /// register in °C.
+    ///
+    /// This is 0.0625°C in both normal and extended measurement mode. The
+    /// extended mode extends the range with an additional bit instead.
+    pub fn temperature_lsb_celsius(&self) -> f32 {
+        0.0625
+    }
+
+    /// Set a calibration offset in °C added to every temperature reading.
+    ///
+    /// This is a software-only correction, for example for sensors mounted
+    /// near a heat source. It is not applied to the temperature thresholds
+    /// nor to the raw temperature samples.
+    pub fn set_calibration_offset(&mut self, offset_c: f32) {
+        self.calibration_offset = offset_c;
+    }
+
+    /// Get the calibration offset in °C added to every temperature reading.
+    pub fn calibration_offset(&self) -> f32 {
+        self.calibration_offset
+    }
+
+    /// Remove the calibration offset.
+    pub fn clear_calibration_offset(&mut self) {
+        self.calibration_offset = 0.0;
+    }
+
+    /// Enable or disable deferring configuration writes while shut down.
+    ///
+    /// When enabled, the configuration setters only update the cached
+    /// configuration while the device is shut down (one-shot mode). The
+    /// whole configuration is then written at once when changing into
+    /// continuous conversion mode, which always writes the configuration
+    /// register. This avoids pointless bus traffic in power-down
+    /// initialization sequences. Temperature thresholds are still written
+    /// immediately.
+    ///
+    /// *Note:* Triggering a one-shot measurement writes the whole cached
+    /// configuration as well, so the deferred settings apply from the next
+    /// one-shot measurement on.
+    ///
+    /// This is disabled per default.
+    pub fn set_deferred_config_writes(&mut self, enabled: bool) {
+        self.defer_config_writes = enabled;
+    }
+
+    /// Enable or disable bracketing configuration changes with a shutdown.
+    ///
+    /// When enabled, changing a configuration field for which
+    /// [`requires_shutdown()`](fn.requires_shutdown.html) returns `true` in
+    /// continuous conversion mode first writes the new configuration with
+    /// the device shut down and then enables it again. This takes an
+    /// additional configuration register write.
+    ///
+    /// This is disabled per default.
+    pub fn set_safe_config_changes(&mut self, enabled: bool) {
+        self.safe_config_changes = enabled;
+    }
+
+    /// Enable or disable alerts caused by the high temperature threshold.
+    ///
+    /// The device has no independent enables for the thresholds: the ALERT
+    /// pin and bit are always governed by the window formed by both of them.
+    /// This masking is done in software by
+    /// [`wait_for_unmasked_alert()`](#method.wait_for_unmasked_alert) only.
+    ///
+    /// This is enabled per default.
+    pub fn set_alert_on_high(&mut self, enabled: bool) {
+        self.alert_enabled.1 = enabled;
+    }
+
+    /// Enable or disable alerts caused by the low temperature threshold.
+    ///
+    /// See [`set_alert_on_high()`](#method.set_alert_on_high) for details.
+    ///
+    /// This is enabled per default.
+    pub fn set_alert_on_low(&mut self, enabled: bool) {
+        self.alert_enabled.0 = enabled;
+    }
+
     /// Reset the internal state of this driver to the default values.
     ///
     /// *Note:* This does not alter the state or configuration of the device.
@@ -216,21 +737,234 @@ where
     /// This needs to be called after performing a reset on the device, for
     /// example through an I2C general-call Reset command, which was not done
     /// through this driver to ensure that the configurations in the device
-    /// and in the driver match.
+    /// and in the driver match. It also forgets the register the device
+    /// pointer was last set to and resets the cached temperature thresholds.
     pub fn reset_internal_driver_state(&mut self) {
         self.config = Config::default();
+        self.config_dirty = false;
+        self.pointer = None;
+        self.thresholds_raw = DEFAULT_THRESHOLDS_RAW;
+    }
+
+    /// Write raw contents to a register.
+    ///
+    /// The data is sent in the order expected by the device, that is, most
+    /// significant byte first.
+    ///
+    /// Writing the configuration register updates the configuration cached
+    /// in the driver. However, the conversion mode must only be changed
+    /// through [`into_one_shot()`](#method.into_one_shot) and
+    /// [`into_continuous()`](#method.into_continuous).
+    pub async fn write_register(
+        &mut self,
+        register: Register,
+        data: [u8; 2],
+    ) -> Result<(), Error<E>> {
+        let [msb, lsb] = data;
+        if register == Register::Config {
+            self.write_config(lsb, msb).await
+        } else {
+            self.write_register_bytes(register, lsb, msb).await
+        }
+    }
+
+    /// Set the device register pointer without writing any data.
+    ///
+    /// Following reads with
+    /// [`read_current_register()`](#method.read_current_register) return
+    /// the contents of this register.
+    pub async fn set_pointer(&mut self, register: Register) -> Result<(), Error<E>> {
+        self.write_dirty_config().await?;
+        let payload = [register as u8];
+        trace!("write to {:?}: {:?}", self.address, payload);
+        self.pointer = None;
+        self.i2c
+            .write(self.address, &payload)
+            .await
+            .map_err(Error::I2C)?;
+        self.pointer = Some(register);
+        Ok(())
+    }
+
+    async fn apply_config(&mut self, config: Config) -> Result<(), Error<E>> {
+        let stays_shut_down =
+            self.config.contains(Config::SHUTDOWN) && config.contains(Config::SHUTDOWN);
+        if self.defer_config_writes && stays_shut_down {
+            self.config = config;
+            return Ok(());
+        }
+        self.force_config(config).await
+    }
+
+    async fn force_config(&mut self, config: Config) -> Result<(), Error<E>> {
+        let stays_continuous =
+            !self.config.contains(Config::SHUTDOWN) && !config.contains(Config::SHUTDOWN);
+        if self.safe_config_changes && stays_continuous && self.config.requires_shutdown_for(config)
+        {
+            let shut_down = config.with_shutdown(true);
+            self.write_config(shut_down.lsb, shut_down.msb).await?;
+        }
+        self.write_config(config.lsb, config.msb).await
     }
 
     async fn write_config(&mut self, lsb: u8, msb: u8) -> Result<(), Error<E>> {
-        self.write_register(Register::CONFIG, lsb, msb).await?;
+        self.send_register_bytes(Register::Config, lsb, msb).await?;
         self.config = Config { lsb, msb };
+        self.config_dirty = false;
         Ok(())
     }
 
-    async fn write_register(&mut self, register: u8, lsb: u8, msb: u8) -> Result<(), Error<E>> {
+    /// Write the cached configuration if the device may not have it.
+    ///
+    /// This is the case after a `ConfigGuard` was dropped without restoring
+    /// the configuration.
+    pub(crate) async fn write_dirty_config(&mut self) -> Result<(), Error<E>> {
+        if self.config_dirty {
+            let Config { lsb, msb } = self.config;
+            self.write_config(lsb, msb).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_register_bytes(
+        &mut self,
+        register: Register,
+        lsb: u8,
+        msb: u8,
+    ) -> Result<(), Error<E>> {
+        self.write_dirty_config().await?;
+        self.send_register_bytes(register, lsb, msb).await
+    }
+
+    async fn send_register_bytes(
+        &mut self,
+        register: Register,
+        lsb: u8,
+        msb: u8,
+    ) -> Result<(), Error<E>> {
+        let payload = register_write(register, lsb, msb);
+        trace!("write to {:?}: {:?}", self.address, payload);
+        self.pointer = None;
         self.i2c
-            .write(self.address, &[register, msb, lsb])
+            .write(self.address, &payload)
             .await
-            .map_err(Error::I2C)
+            .map_err(Error::I2C)?;
+        self.pointer = Some(register);
+        let value = u16::from_be_bytes([msb, lsb]);
+        match register {
+            Register::TLow => self.thresholds_raw.0 = value,
+            Register::THigh => self.thresholds_raw.1 = value,
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+fn fahrenheit_to_celsius(fahrenheit: f32) -> f32 {
+    (fahrenheit - 32.0) / 1.8
+}
+
+impl Config {
+    fn requires_shutdown_for(self, new: Config) -> bool {
+        let changed = self.bits() ^ new.bits();
+        ConfigField::all()
+            .iter()
+            .any(|&field| requires_shutdown(field) && (changed & field.mask()) != 0)
+    }
+
+    /// Take the settings of `target`, keeping the conversion mode and the
+    /// read-only bits.
+    pub(crate) fn with_settings_of(self, target: Config) -> Self {
+        let kept_lsb = BFL::SHUTDOWN | BFL::RESOLUTION;
+        Config {
+            lsb: (target.lsb & !kept_lsb & !BFL::ONE_SHOT) | (self.lsb & kept_lsb),
+            msb: (target.msb & !BFH::ALERT) | (self.msb & BFH::ALERT),
+        }
+    }
+
+    pub(crate) fn with_shutdown(self, shutdown: bool) -> Self {
+        self.with_lsb_flags(BFL::SHUTDOWN, shutdown)
+    }
+
+    fn with_change(self, change: ConfigChange) -> Self {
+        match change {
+            ConfigChange::ConversionRate(rate) => self.with_conversion_rate(rate),
+            ConfigChange::ExtendedMode(enabled) => self.with_extended_mode(enabled),
+            ConfigChange::FaultQueue(fq) => self.with_fault_queue(fq),
+            ConfigChange::AlertPolarity(polarity) => self.with_alert_polarity(polarity),
+            ConfigChange::ThermostatMode(mode) => self.with_thermostat_mode(mode),
+        }
+    }
+
+    pub(crate) fn with_extended_mode(self, enabled: bool) -> Self {
+        self.with_msb_flags(BFH::EXTENDED_MODE, enabled)
+    }
+
+    pub(crate) fn with_conversion_rate(self, rate: CR) -> Self {
+        let (cr1, cr0) = match rate {
+            CR::_0_25Hz => (false, false),
+            CR::_1Hz => (false, true),
+            CR::_4Hz => (true, false),
+            CR::_8Hz => (true, true),
+        };
+        self.with_msb_flags(BFH::CONV_RATE1, cr1)
+            .with_msb_flags(BFH::CONV_RATE0, cr0)
+    }
+
+    pub(crate) fn with_fault_queue(self, fq: FaultQueue) -> Self {
+        let (f1, f0) = match fq {
+            FaultQueue::_1 => (false, false),
+            FaultQueue::_2 => (false, true),
+            FaultQueue::_4 => (true, false),
+            FaultQueue::_6 => (true, true),
+        };
+        self.with_lsb_flags(BFL::FAULT_QUEUE1, f1)
+            .with_lsb_flags(BFL::FAULT_QUEUE0, f0)
+    }
+
+    pub(crate) fn with_alert_polarity(self, polarity: AlertPolarity) -> Self {
+        self.with_lsb_flags(BFL::ALERT_POLARITY, polarity == AlertPolarity::ActiveHigh)
+    }
+
+    pub(crate) fn with_thermostat_mode(self, mode: ThermostatMode) -> Self {
+        self.with_lsb_flags(BFL::THERMOSTAT, mode == ThermostatMode::Interrupt)
+    }
+
+    /// Encode a temperature threshold as `(msb, lsb)` for the measurement
+    /// mode in effect.
+    pub(crate) fn threshold_bytes(&self, temperature: f32) -> (u8, u8) {
+        if self.is_extended_mode() {
+            convert_temp_to_register_extended(temperature)
+        } else {
+            convert_temp_to_register_normal(temperature)
+        }
+    }
+
+    /// Encode a temperature threshold in units of 0.0625°C as `(msb, lsb)`
+    /// for the measurement mode of this configuration.
+    pub(crate) fn threshold_bytes_raw(&self, counts: i16) -> (u8, u8) {
+        if self.is_extended_mode() {
+            convert_raw_to_register_extended(counts)
+        } else {
+            convert_raw_to_register_normal(counts)
+        }
+    }
+
+    fn with_lsb_flags(mut self, flags: u8, set: bool) -> Self {
+        if set {
+            self.lsb |= flags;
+        } else {
+            self.lsb &= !flags;
+        }
+        self
+    }
+
+    fn with_msb_flags(mut self, flags: u8, set: bool) -> Self {
+        if set {
+            self.msb |= flags;
+        } else {
+            self.msb &= !flags;
+        }
+        self
     }
 }
@@ -1,6 +1,17 @@
 // functions to convert temperature to and from register values
 
+use crate::{EXTENDED_MAX_C, EXTENDED_MIN_C, NORMAL_MAX_C, NORMAL_MIN_C};
+
 pub fn convert_temp_from_register(msb: u8, lsb: u8) -> f32 {
+    f32::from(convert_temp_from_register_raw(msb, lsb)) * 0.0625
+}
+
+// temperature in units of 0.0625°C
+//
+// This cannot panic: only masked `u16` values are shifted by constant amounts
+// that do not overflow and no indexing is done. All 65536 register values are
+// checked in the tests.
+pub fn convert_temp_from_register_raw(msb: u8, lsb: u8) -> i16 {
     let mut sign = (u16::from(msb & 0b1000_0000)) << 8;
     let extended_mode = (lsb & 1) != 0;
     if extended_mode {
@@ -10,7 +21,7 @@ pub fn convert_temp_from_register(msb: u8, lsb: u8) -> f32 {
         let msb = u16::from(msb & 0b0111_1111);
         let value = sign | (msb << 5) | u16::from(lsb >> 3);
         // the value is stored as two's complement
-        f32::from(value as i16) * 0.0625
+        value as i16
     } else {
         if sign != 0 {
             sign |= 0b1111_1000 << 8;
@@ -18,41 +29,122 @@ pub fn convert_temp_from_register(msb: u8, lsb: u8) -> f32 {
         let msb = u16::from(msb & 0b0111_1111);
         let value = sign | (msb << 4) | u16::from(lsb >> 4);
         // the value is stored as two's complement
-        f32::from(value as i16) * 0.0625
+        value as i16
     }
 }
 
-pub fn convert_temp_to_register_normal(mut t: f32) -> (u8, u8) {
-    if t > 127.9375 {
-        t = 127.9375;
+/// Decode the contents of a temperature or threshold register in °C.
+///
+/// The registers are sent big-endian over the bus: `msb` is the first byte
+/// sent by the device and `lsb` the second one. Swapping them is a common
+/// mistake when decoding captured bus traces. The value is left-aligned
+/// two's complement in units of 0.0625°C with 12 bits in normal mode and
+/// 13 bits in extended mode. Whether the extended format is used must be
+/// given, since only the temperature register carries the extended mode
+/// flag in its least significant bit.
+///
+/// This is provided as a reference decoder for validating bus traces.
+pub fn decode_temperature(msb: u8, lsb: u8, extended_mode: bool) -> f32 {
+    convert_temp_from_register(msb, (lsb & !1) | extended_mode as u8)
+}
+
+// temperature in units of 0.0625°C, rounded half away from zero to the
+// given number of decimal places. 0.0625 = 625e-4 so four decimal places
+// are exact and more are returned unchanged.
+pub fn round_temperature(raw: i16, digits: u8) -> f32 {
+    let ten_thousandths = i32::from(raw) * 625;
+    if digits >= 4 {
+        return ten_thousandths as f32 / 10_000.0;
     }
-    if t < -128.0 {
-        t = -128.0
+    let divisor = 10_i32.pow(4 - u32::from(digits));
+    let half = if ten_thousandths < 0 {
+        -divisor / 2
+    } else {
+        divisor / 2
+    };
+    let rounded = (ten_thousandths + half) / divisor;
+    rounded as f32 / 10_i32.pow(u32::from(digits)) as f32
+}
+
+/// Conversion from a raw temperature register value.
+///
+/// The raw value is the temperature in units of 0.0625°C. See
+/// [`read_as()`](struct.Tmp1x2.html#method.read_as).
+pub trait FromRawTemperature {
+    /// Convert from a temperature in units of 0.0625°C.
+    fn from_raw_temperature(raw: i16) -> Self;
+}
+
+/// Temperature in °C.
+impl FromRawTemperature for f32 {
+    fn from_raw_temperature(raw: i16) -> Self {
+        f32::from(raw) * 0.0625
+    }
+}
+
+/// Temperature in units of 0.0625°C, as stored in the device.
+impl FromRawTemperature for i16 {
+    fn from_raw_temperature(raw: i16) -> Self {
+        raw
     }
-    let value = t / 0.0625;
-    let value = (value as i16) << 4;
-    ((value >> 8) as u8, (value as u8 & 0b1111_0000))
 }
 
-pub fn convert_temp_to_register_extended(mut t: f32) -> (u8, u8) {
-    if t > 255.875 {
-        t = 255.875;
+/// Temperature in m°C.
+impl FromRawTemperature for i32 {
+    fn from_raw_temperature(raw: i16) -> Self {
+        i32::from(raw) * 625 / 10
     }
-    if t < -256.0 {
-        t = -256.0
+}
+
+// Temperatures are rounded to the nearest multiple of 0.0625°C with ties to
+// even in both modes, so that positive and negative values round
+// symmetrically. Truncation would shift negative values towards zero.
+pub fn convert_temp_to_register_normal(t: f32) -> (u8, u8) {
+    let t = t.clamp(NORMAL_MIN_C, NORMAL_MAX_C);
+    convert_raw_to_register_normal(round_half_even(t / 0.0625))
+}
+
+pub fn convert_temp_to_register_extended(t: f32) -> (u8, u8) {
+    let t = t.clamp(EXTENDED_MIN_C, EXTENDED_MAX_C);
+    convert_raw_to_register_extended(round_half_even(t / 0.0625))
+}
+
+// no `f32::round()` in `no_std`
+fn round_half_even(x: f32) -> i16 {
+    let truncated = x as i16;
+    let fraction = x - f32::from(truncated);
+    let is_odd = truncated % 2 != 0;
+    if fraction > 0.5 || (fraction == 0.5 && is_odd) {
+        truncated + 1
+    } else if fraction < -0.5 || (fraction == -0.5 && is_odd) {
+        truncated - 1
+    } else {
+        truncated
     }
-    let value = t / 0.0625;
-    let value = (value as i16) << 3;
+}
+
+// temperature in units of 0.0625°C, capped to 12 bits
+pub fn convert_raw_to_register_normal(counts: i16) -> (u8, u8) {
+    let value = counts.clamp(-2048, 2047) << 4;
+    ((value >> 8) as u8, (value as u8 & 0b1111_0000))
+}
+
+// temperature in units of 0.0625°C, capped to 13 bits
+pub fn convert_raw_to_register_extended(counts: i16) -> (u8, u8) {
+    let value = counts.clamp(-4096, 4095) << 3;
     ((value >> 8) as u8, (value as u8 & 0b1111_1000))
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
+        convert_raw_to_register_extended, convert_raw_to_register_normal,
         convert_temp_from_register as convert_from_reg,
+        convert_temp_from_register_raw as convert_from_reg_raw,
         convert_temp_to_register_extended as convert_to_reg_ext,
-        convert_temp_to_register_normal as convert_to_reg,
+        convert_temp_to_register_normal as convert_to_reg, decode_temperature, round_temperature,
     };
+    use super::{EXTENDED_MAX_C, EXTENDED_MIN_C, NORMAL_MAX_C, NORMAL_MIN_C};
 
     macro_rules! assert_near {
         ($left:expr, $right:expr) => {
@@ -88,6 +180,54 @@ mod tests {
         assert_near!(-128.0, convert_from_reg(0b1000_0000, 0b0000_0000));
     }
 
+    #[test]
+    fn can_convert_raw_to_register() {
+        assert_eq!(
+            (0b0111_1111, 0b1111_0000),
+            convert_raw_to_register_normal(2047)
+        );
+        assert_eq!(
+            (0b0111_1111, 0b1111_0000),
+            convert_raw_to_register_normal(4000)
+        );
+        assert_eq!((0b1110_0111, 0), convert_raw_to_register_normal(-400));
+        assert_eq!((0b1000_0000, 0), convert_raw_to_register_normal(-3000));
+        assert_eq!(
+            (0b0111_1111, 0b1111_1000),
+            convert_raw_to_register_extended(4095)
+        );
+        assert_eq!(
+            (0b1111_0011, 0b1000_0000),
+            convert_raw_to_register_extended(-400)
+        );
+        assert_eq!((0b1000_0000, 0), convert_raw_to_register_extended(-5000));
+    }
+
+    #[test]
+    fn can_decode_datasheet_examples() {
+        // normal mode: 0x640, 0x190, 0x004, 0xFFC and 0xC90
+        assert_near!(100.0, decode_temperature(0x64, 0x00, false));
+        assert_near!(25.0, decode_temperature(0x19, 0x00, false));
+        assert_near!(0.25, decode_temperature(0x00, 0x40, false));
+        assert_near!(-0.25, decode_temperature(0xFF, 0xC0, false));
+        assert_near!(-55.0, decode_temperature(0xC9, 0x00, false));
+        // extended mode: 0x0800, 0x0960 and 0x1C90
+        assert_near!(128.0, decode_temperature(0x40, 0x00, true));
+        assert_near!(150.0, decode_temperature(0x4B, 0x00, true));
+        assert_near!(-55.0, decode_temperature(0xE4, 0x80, true));
+    }
+
+    #[test]
+    fn can_decode_threshold_register() {
+        assert_near!(
+            127.9375,
+            decode_temperature(0b0111_1111, 0b1111_0000, false)
+        );
+        assert_near!(-25.0, decode_temperature(0b1110_0111, 0b0000_0000, false));
+        assert_near!(255.875, decode_temperature(0b0111_1111, 0b1111_0000, true));
+        assert_near!(-25.0, decode_temperature(0b1111_0011, 0b1000_0000, true));
+    }
+
     #[test]
     fn can_convert_temperature_from_register_extended_mode() {
         assert_near!(255.875, convert_from_reg(0b0111_1111, 0b1111_0001));
@@ -107,14 +247,94 @@ mod tests {
         assert_near!(-256.0, convert_from_reg(0b1000_0000, 0b0000_0001));
     }
 
+    #[test]
+    fn all_register_values_convert_within_range() {
+        for value in 0..=u16::MAX {
+            let [msb, lsb] = value.to_be_bytes();
+            let temperature = convert_from_reg(msb, lsb);
+            let (min, max) = if lsb & 1 != 0 {
+                (EXTENDED_MIN_C, EXTENDED_MAX_C)
+            } else {
+                (NORMAL_MIN_C, NORMAL_MAX_C)
+            };
+            assert!(
+                (min..=max).contains(&temperature),
+                "{:#06x} converted to {}",
+                value,
+                temperature
+            );
+            assert_eq!(temperature, convert_from_reg_raw(msb, lsb) as f32 * 0.0625);
+        }
+    }
+
+    #[test]
+    fn non_finite_temperatures_convert_without_panic() {
+        assert_eq!((0, 0), convert_to_reg(f32::NAN));
+        assert_eq!((0, 0), convert_to_reg_ext(f32::NAN));
+        assert_eq!((0b0111_1111, 0b1111_0000), convert_to_reg(f32::INFINITY));
+        assert_eq!((0b1000_0000, 0), convert_to_reg_ext(f32::NEG_INFINITY));
+    }
+
+    #[test]
+    fn can_convert_raw_temperature_from_register() {
+        assert_eq!(2047, convert_from_reg_raw(0b0111_1111, 0b1111_0000));
+        assert_eq!(400, convert_from_reg_raw(0b0001_1001, 0b0000_0000));
+        assert_eq!(-4, convert_from_reg_raw(0b1111_1111, 0b1100_0000));
+        assert_eq!(-2048, convert_from_reg_raw(0b1000_0000, 0b0000_0000));
+        assert_eq!(4094, convert_from_reg_raw(0b0111_1111, 0b1111_0001));
+        assert_eq!(-4096, convert_from_reg_raw(0b1000_0000, 0b0000_0001));
+    }
+
+    #[test]
+    fn can_round_temperature() {
+        assert_near!(25.0, round_temperature(401, 0));
+        assert_near!(25.1, round_temperature(401, 1));
+        assert_near!(25.06, round_temperature(401, 2));
+        assert_near!(25.063, round_temperature(401, 3));
+        assert_near!(25.0625, round_temperature(401, 4));
+        assert_near!(25.0625, round_temperature(401, 9));
+        assert_near!(-10.1, round_temperature(-161, 1));
+        assert_near!(-10.06, round_temperature(-161, 2));
+        assert_near!(0.0, round_temperature(0, 2));
+    }
+
     #[test]
     fn values_are_clamped() {
         assert_eq!((0b0111_1111, 0b1111_0000), convert_to_reg(129.0));
         assert_eq!((0b1000_0000, 0b0000_0000), convert_to_reg(-129.0));
-        assert_eq!((0b0111_1111, 0b1111_0000), convert_to_reg_ext(256.0));
+        assert_eq!((0b0111_1111, 0b1111_1000), convert_to_reg_ext(256.0));
         assert_eq!((0b1000_0000, 0b0000_0000), convert_to_reg_ext(-257.0));
     }
 
+    #[test]
+    fn values_are_clamped_to_range_constants() {
+        assert_eq!(
+            convert_to_reg(NORMAL_MAX_C),
+            convert_to_reg(NORMAL_MAX_C + 1.0)
+        );
+        assert_eq!(
+            convert_to_reg(NORMAL_MIN_C),
+            convert_to_reg(NORMAL_MIN_C - 1.0)
+        );
+        let (msb, lsb) = convert_to_reg(NORMAL_MAX_C);
+        assert_near!(NORMAL_MAX_C, convert_from_reg(msb, lsb));
+        let (msb, lsb) = convert_to_reg(NORMAL_MIN_C);
+        assert_near!(NORMAL_MIN_C, convert_from_reg(msb, lsb));
+
+        assert_eq!(
+            convert_to_reg_ext(EXTENDED_MAX_C),
+            convert_to_reg_ext(EXTENDED_MAX_C + 1.0)
+        );
+        assert_eq!(
+            convert_to_reg_ext(EXTENDED_MIN_C),
+            convert_to_reg_ext(EXTENDED_MIN_C - 1.0)
+        );
+        let (msb, lsb) = convert_to_reg_ext(EXTENDED_MAX_C);
+        assert_near!(EXTENDED_MAX_C, convert_from_reg(msb, lsb | 1));
+        let (msb, lsb) = convert_to_reg_ext(EXTENDED_MIN_C);
+        assert_near!(EXTENDED_MIN_C, convert_from_reg(msb, lsb | 1));
+    }
+
     #[test]
     fn can_convert_temperature_to_register_normal_mode() {
         assert_eq!((0b0111_1111, 0b1111_0000), convert_to_reg(128.0));
@@ -132,8 +352,30 @@ mod tests {
         assert_eq!((0b1000_0000, 0b0000_0000), convert_to_reg(-128.0));
     }
 
+    #[test]
+    fn rounds_to_nearest_with_ties_to_even() {
+        // -0.5 LSB
+        assert_eq!((0, 0), convert_to_reg(-0.03125));
+        assert_eq!((0, 0), convert_to_reg_ext(-0.03125));
+        // 1.5 LSB
+        assert_eq!((0, 0b0010_0000), convert_to_reg(0.09375));
+        assert_eq!((0, 0b0001_0000), convert_to_reg_ext(0.09375));
+        // -1.5 LSB
+        assert_eq!((0b1111_1111, 0b1110_0000), convert_to_reg(-0.09375));
+        assert_eq!((0b1111_1111, 0b1111_0000), convert_to_reg_ext(-0.09375));
+        // 2.5 LSB
+        assert_eq!((0, 0b0010_0000), convert_to_reg(0.15625));
+        // 0.6 and -0.6 LSB
+        assert_eq!((0, 0b0001_0000), convert_to_reg(0.0375));
+        assert_eq!((0b1111_1111, 0b1111_0000), convert_to_reg(-0.0375));
+        // 0.4 and -0.4 LSB
+        assert_eq!((0, 0), convert_to_reg(0.025));
+        assert_eq!((0, 0), convert_to_reg(-0.025));
+    }
+
     #[test]
     fn can_convert_temperature_to_register_extended_mode() {
+        assert_eq!((0b0111_1111, 0b1111_1000), convert_to_reg_ext(255.9375));
         assert_eq!((0b0111_1111, 0b1111_0000), convert_to_reg_ext(255.875));
         assert_eq!((0b0100_1011, 0b0000_0000), convert_to_reg_ext(150.0));
         assert_eq!((0b0100_0000, 0b0000_0000), convert_to_reg_ext(128.0));
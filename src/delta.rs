@@ -0,0 +1,93 @@
+use crate::{
+    marker::{mode, unit::Unit},
+    Error, Tmp1x2,
+};
+use embedded_hal_async::i2c;
+
+/// Notification of temperature changes exceeding a configured delta.
+///
+/// The devices can only alert when crossing fixed thresholds. This emulates
+/// an alert on temperature changes by comparing periodic readings in
+/// continuous conversion mode to the temperature reported last.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeltaAlert {
+    delta: f32,
+    last_reported: Option<f32>,
+}
+
+impl DeltaAlert {
+    /// Create a new alert reporting changes of more than `delta`.
+    ///
+    /// The delta is in the unit of the driver instance polled.
+    pub const fn new(delta: f32) -> Self {
+        DeltaAlert {
+            delta,
+            last_reported: None,
+        }
+    }
+
+    /// Read the temperature and return it if it changed by more than the
+    /// delta since it was last returned.
+    ///
+    /// The first reading is always returned and serves as reference. The
+    /// calibration offset is added as in
+    /// [`read_temperature()`](struct.Tmp1x2.html#method.read_temperature).
+    pub async fn poll<I2C, E, UNIT>(
+        &mut self,
+        dev: &mut Tmp1x2<I2C, mode::Continuous, UNIT>,
+    ) -> Result<Option<f32>, Error<E>>
+    where
+        I2C: i2c::I2c<Error = E>,
+        UNIT: Unit,
+    {
+        let temperature = dev.read_temperature().await?;
+        Ok(self.update(temperature))
+    }
+
+    /// Get the temperature returned last, if any.
+    pub fn last_reported(&self) -> Option<f32> {
+        self.last_reported
+    }
+
+    /// Forget the temperature returned last.
+    pub fn reset(&mut self) {
+        self.last_reported = None;
+    }
+
+    fn update(&mut self, temperature: f32) -> Option<f32> {
+        match self.last_reported {
+            // no `f32::abs()` in `no_std`
+            Some(last) if (-self.delta..=self.delta).contains(&(temperature - last)) => None,
+            _ => {
+                self.last_reported = Some(temperature);
+                Some(temperature)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeltaAlert;
+
+    #[test]
+    fn reports_changes_exceeding_delta() {
+        let mut alert = DeltaAlert::new(1.0);
+        assert_eq!(Some(25.0), alert.update(25.0));
+        assert_eq!(None, alert.update(25.5));
+        assert_eq!(None, alert.update(26.0));
+        assert_eq!(Some(26.0625), alert.update(26.0625));
+        assert_eq!(None, alert.update(25.5));
+        assert_eq!(Some(25.0), alert.update(25.0));
+        assert_eq!(Some(25.0), alert.last_reported());
+    }
+
+    #[test]
+    fn reports_again_after_reset() {
+        let mut alert = DeltaAlert::new(1.0);
+        assert_eq!(Some(25.0), alert.update(25.0));
+        alert.reset();
+        assert_eq!(None, alert.last_reported());
+        assert_eq!(Some(25.0), alert.update(25.0));
+    }
+}
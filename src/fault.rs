@@ -0,0 +1,104 @@
+use crate::FaultQueue;
+
+/// Software model of the consecutive fault counter of the device.
+///
+/// The device asserts an alert only after the number of consecutive faults
+/// selected with the fault queue setting. This mirrors that counter so that
+/// the number of faults remaining until an alert can be predicted or logged.
+///
+/// Whether a reading is a fault is decided by the caller, for example by
+/// comparing it to the temperature thresholds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultTracker {
+    queue: FaultQueue,
+    faults: u8,
+}
+
+impl FaultTracker {
+    /// Create a new tracker for the given fault queue setting with no
+    /// faults recorded.
+    pub const fn new(queue: FaultQueue) -> Self {
+        FaultTracker { queue, faults: 0 }
+    }
+
+    /// Record whether the latest reading was a fault.
+    ///
+    /// A reading without a fault resets the count of consecutive faults.
+    /// Returns whether an alert would be asserted.
+    pub fn update(&mut self, fault: bool) -> bool {
+        self.faults = if fault {
+            self.faults.saturating_add(1).min(self.queue.depth())
+        } else {
+            0
+        };
+        self.is_alert_active()
+    }
+
+    /// Get the number of consecutive faults recorded, up to the fault queue
+    /// depth.
+    pub fn faults(&self) -> u8 {
+        self.faults
+    }
+
+    /// Get the number of further consecutive faults until an alert is
+    /// asserted.
+    ///
+    /// Returns 0 if an alert would already be asserted.
+    pub fn pending_faults(&self) -> u8 {
+        self.queue.depth() - self.faults
+    }
+
+    /// Get whether an alert would be asserted.
+    pub fn is_alert_active(&self) -> bool {
+        self.pending_faults() == 0
+    }
+
+    /// Clear the recorded faults.
+    pub fn reset(&mut self) {
+        self.faults = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FaultTracker;
+    use crate::FaultQueue;
+
+    macro_rules! fault_sequence_test {
+        ($name:ident, $queue:ident, $depth:expr) => {
+            #[test]
+            fn $name() {
+                let mut tracker = FaultTracker::new(FaultQueue::$queue);
+                assert_eq!($depth, tracker.pending_faults());
+                for pending in (1..$depth).rev() {
+                    assert!(!tracker.update(true));
+                    assert_eq!(pending, tracker.pending_faults());
+                }
+                assert!(tracker.update(true));
+                assert_eq!(0, tracker.pending_faults());
+                assert!(tracker.update(true));
+                assert_eq!($depth, tracker.faults());
+                assert!(!tracker.update(false));
+                assert_eq!($depth, tracker.pending_faults());
+            }
+        };
+    }
+
+    fault_sequence_test!(can_track_faults_queue_1, _1, 1);
+    fault_sequence_test!(can_track_faults_queue_2, _2, 2);
+    fault_sequence_test!(can_track_faults_queue_4, _4, 4);
+    fault_sequence_test!(can_track_faults_queue_6, _6, 6);
+
+    #[test]
+    fn interrupted_faults_restart_count() {
+        let mut tracker = FaultTracker::new(FaultQueue::_4);
+        tracker.update(true);
+        tracker.update(true);
+        tracker.update(false);
+        tracker.update(true);
+        assert_eq!(1, tracker.faults());
+        assert_eq!(3, tracker.pending_faults());
+        tracker.reset();
+        assert_eq!(4, tracker.pending_faults());
+    }
+}
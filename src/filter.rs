@@ -0,0 +1,295 @@
+use crate::{
+    marker::{mode, unit::Unit},
+    Error, Tmp1x2,
+};
+use embedded_hal_async::i2c;
+
+/// Moving average over the last `N` temperature readings.
+///
+/// The readings are stored as raw register counts and averaged with integer
+/// arithmetic so that no floating point error accumulates over time.
+#[derive(Debug, Clone)]
+pub struct Averager<const N: usize> {
+    samples: [i16; N],
+    len: usize,
+    next: usize,
+}
+
+impl<const N: usize> Default for Averager<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Averager<N> {
+    const NOT_EMPTY: () = assert!(N > 0, "Averager needs space for at least one reading");
+
+    /// Create a new moving average with no readings.
+    ///
+    /// `N` must not be zero, which is checked at compile time.
+    ///
+    /// ```compile_fail
+    /// let filter = tmp1x2::Averager::<0>::new();
+    /// ```
+    pub const fn new() -> Self {
+        // referencing the constant fails the build for `N == 0`
+        #[allow(clippy::let_unit_value)]
+        let () = Self::NOT_EMPTY;
+        Averager {
+            samples: [0; N],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Read a temperature sample from the device and return the average of
+    /// the last `N` readings in the unit of the driver instance.
+    ///
+    /// Until `N` readings are available, the average of all readings so far
+    /// is returned. The calibration offset is added as in
+    /// [`read_temperature()`](struct.Tmp1x2.html#method.read_temperature).
+    pub async fn read_averaged<I2C, E, UNIT>(
+        &mut self,
+        dev: &mut Tmp1x2<I2C, mode::Continuous, UNIT>,
+    ) -> Result<f32, Error<E>>
+    where
+        I2C: i2c::I2c<Error = E>,
+        UNIT: Unit,
+    {
+        let counts = dev.read_temperature_counts().await?;
+        self.push(counts);
+        let celsius = self.average() + dev.calibration_offset();
+        Ok(UNIT::UNIT.convert_celsius(celsius))
+    }
+
+    fn push(&mut self, counts: i16) {
+        self.samples[self.next] = counts;
+        self.next = (self.next + 1) % N;
+        if self.len < N {
+            self.len += 1;
+        }
+    }
+
+    fn average(&self) -> f32 {
+        let sum: i32 = self.samples[..self.len].iter().map(|&s| i32::from(s)).sum();
+        sum as f32 / self.len as f32 * 0.0625
+    }
+}
+
+/// Moving median over the last `N` temperature readings.
+///
+/// The median is more robust than the mean against single-sample glitches
+/// like bus errors that went undetected. It is computed by sorting a copy of
+/// the readings on the stack, so `N` should be small.
+#[derive(Debug, Clone)]
+pub struct MedianFilter<const N: usize> {
+    samples: [i16; N],
+    len: usize,
+    next: usize,
+}
+
+impl<const N: usize> Default for MedianFilter<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> MedianFilter<N> {
+    const NOT_EMPTY: () = assert!(N > 0, "MedianFilter needs space for at least one reading");
+
+    /// Create a new moving median with no readings.
+    ///
+    /// `N` must not be zero, which is checked at compile time.
+    ///
+    /// ```compile_fail
+    /// let filter = tmp1x2::MedianFilter::<0>::new();
+    /// ```
+    pub const fn new() -> Self {
+        // referencing the constant fails the build for `N == 0`
+        #[allow(clippy::let_unit_value)]
+        let () = Self::NOT_EMPTY;
+        MedianFilter {
+            samples: [0; N],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Read a temperature sample from the device and return the median of
+    /// the last `N` readings in the unit of the driver instance.
+    ///
+    /// Until `N` readings are available, the median of all readings so far
+    /// is returned. For an even number of readings, the mean of the two
+    /// middle readings is returned. The calibration offset is added as in
+    /// [`read_temperature()`](struct.Tmp1x2.html#method.read_temperature).
+    pub async fn read_temperature_median<I2C, E, UNIT>(
+        &mut self,
+        dev: &mut Tmp1x2<I2C, mode::Continuous, UNIT>,
+    ) -> Result<f32, Error<E>>
+    where
+        I2C: i2c::I2c<Error = E>,
+        UNIT: Unit,
+    {
+        let counts = dev.read_temperature_counts().await?;
+        self.push(counts);
+        let celsius = self.median() + dev.calibration_offset();
+        Ok(UNIT::UNIT.convert_celsius(celsius))
+    }
+
+    fn push(&mut self, counts: i16) {
+        self.samples[self.next] = counts;
+        self.next = (self.next + 1) % N;
+        if self.len < N {
+            self.len += 1;
+        }
+    }
+
+    fn median(&self) -> f32 {
+        let mut sorted = self.samples;
+        let sorted = &mut sorted[..self.len];
+        sorted.sort_unstable();
+        let middle = self.len / 2;
+        if self.len % 2 == 0 {
+            let sum = i32::from(sorted[middle - 1]) + i32::from(sorted[middle]);
+            sum as f32 / 2.0 * 0.0625
+        } else {
+            f32::from(sorted[middle]) * 0.0625
+        }
+    }
+}
+
+/// Exponentially weighted moving average of the temperature readings.
+///
+/// Each reading `x` updates the average as `alpha * x + (1 - alpha) *
+/// average`. An `alpha` close to 1 follows the readings quickly, while an
+/// `alpha` close to 0 smooths them strongly. The first reading initializes
+/// the average.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ewma {
+    alpha: f32,
+    average: Option<f32>,
+}
+
+impl Ewma {
+    /// Create a new moving average with the given weight of new readings.
+    ///
+    /// Returns `None` if `alpha` is not in the interval `(0.0, 1.0]`.
+    pub fn new(alpha: f32) -> Option<Self> {
+        if alpha > 0.0 && alpha <= 1.0 {
+            Some(Ewma {
+                alpha,
+                average: None,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Read the temperature from the device and return the updated average
+    /// in the unit of the driver instance.
+    ///
+    /// The calibration offset is added to the readings as in
+    /// [`read_temperature()`](struct.Tmp1x2.html#method.read_temperature).
+    pub async fn read_temperature_ewma<I2C, E, UNIT>(
+        &mut self,
+        dev: &mut Tmp1x2<I2C, mode::Continuous, UNIT>,
+    ) -> Result<f32, Error<E>>
+    where
+        I2C: i2c::I2c<Error = E>,
+        UNIT: Unit,
+    {
+        let temperature = dev.read_temperature().await?;
+        Ok(self.update(temperature))
+    }
+
+    /// Get the current average, if any reading was made.
+    pub fn average(&self) -> Option<f32> {
+        self.average
+    }
+
+    /// Forget the current average.
+    pub fn reset(&mut self) {
+        self.average = None;
+    }
+
+    fn update(&mut self, temperature: f32) -> f32 {
+        let average = match self.average {
+            Some(average) => self.alpha * temperature + (1.0 - self.alpha) * average,
+            None => temperature,
+        };
+        self.average = Some(average);
+        average
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Averager, Ewma, MedianFilter};
+
+    #[test]
+    fn averages_last_readings() {
+        let mut averager = Averager::<3>::new();
+        averager.push(400);
+        assert_eq!(25.0, averager.average());
+        averager.push(416);
+        assert_eq!(25.5, averager.average());
+        averager.push(432);
+        assert_eq!(26.0, averager.average());
+        averager.push(448);
+        assert_eq!(27.0, averager.average());
+    }
+
+    #[test]
+    fn averages_negative_readings() {
+        let mut averager = Averager::<2>::new();
+        averager.push(-4);
+        averager.push(4);
+        assert_eq!(0.0, averager.average());
+        averager.push(-12);
+        assert_eq!(-0.25, averager.average());
+    }
+
+    #[test]
+    fn median_rejects_spikes() {
+        let mut filter = MedianFilter::<3>::new();
+        filter.push(400);
+        assert_eq!(25.0, filter.median());
+        filter.push(1600);
+        assert_eq!(62.5, filter.median());
+        filter.push(401);
+        assert_eq!(25.0625, filter.median());
+        filter.push(-2048);
+        assert_eq!(25.0625, filter.median());
+        filter.push(400);
+        assert_eq!(25.0, filter.median());
+    }
+
+    #[test]
+    fn ewma_converges_towards_step() {
+        let mut ewma = Ewma::new(0.5).unwrap();
+        assert_eq!(20.0, ewma.update(20.0));
+        let mut last = 20.0;
+        for _ in 0..10 {
+            let average = ewma.update(30.0);
+            assert!(average > last && average < 30.0);
+            last = average;
+        }
+        assert!(30.0 - last < 0.01);
+        ewma.reset();
+        assert_eq!(None, ewma.average());
+    }
+
+    #[test]
+    fn ewma_with_alpha_one_follows_readings() {
+        let mut ewma = Ewma::new(1.0).unwrap();
+        ewma.update(20.0);
+        assert_eq!(30.0, ewma.update(30.0));
+    }
+
+    #[test]
+    fn ewma_alpha_must_be_in_range() {
+        assert_eq!(None, Ewma::new(0.0));
+        assert_eq!(None, Ewma::new(1.5));
+        assert_eq!(None, Ewma::new(f32::NAN));
+    }
+}
@@ -0,0 +1,14 @@
+// logging of the I2C transactions when the `trace` feature is enabled
+
+macro_rules! trace {
+    ($($arg:expr),* $(,)?) => {{
+        #[cfg(all(feature = "trace", feature = "defmt"))]
+        defmt::trace!($($arg),*);
+        #[cfg(all(feature = "trace", not(feature = "defmt")))]
+        log::trace!($($arg),*);
+        #[cfg(not(feature = "trace"))]
+        {
+            $(let _ = &$arg;)*
+        }
+    }};
+}
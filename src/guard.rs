@@ -0,0 +1,73 @@
+use crate::{marker::unit::Celsius, Config, Error, Tmp1x2};
+use core::ops::{Deref, DerefMut};
+use embedded_hal_async::i2c;
+
+/// Guard restoring a previous configuration.
+///
+/// This is returned by [`scoped_config()`](struct.Tmp1x2.html#method.scoped_config)
+/// and gives access to the driver so that the configuration can be changed
+/// temporarily. Call [`restore()`](#method.restore) to write the captured
+/// configuration back to the device.
+///
+/// *NOTE*: Restoring the configuration requires an async I²C write, which
+/// cannot be done when the guard is dropped. If the guard is dropped without
+/// calling [`restore()`](#method.restore), for example because an error was
+/// returned early, the captured configuration is written back at the start
+/// of the next operation of the driver that accesses the device.
+#[derive(Debug)]
+#[must_use = "the configuration should be restored by calling `restore()`"]
+pub struct ConfigGuard<'a, I2C, MODE, UNIT = Celsius> {
+    dev: &'a mut Tmp1x2<I2C, MODE, UNIT>,
+    saved: Config,
+}
+
+impl<'a, I2C, E, MODE, UNIT> ConfigGuard<'a, I2C, MODE, UNIT>
+where
+    I2C: i2c::I2c<Error = E>,
+{
+    /// Write the captured configuration back to the device.
+    ///
+    /// No write is done if the configuration was not changed.
+    pub async fn restore(self) -> Result<(), Error<E>> {
+        self.dev.reconfigure(self.saved).await
+    }
+}
+
+impl<'a, I2C, MODE, UNIT> Drop for ConfigGuard<'a, I2C, MODE, UNIT> {
+    fn drop(&mut self) {
+        let config = self.dev.config.with_settings_of(self.saved);
+        if config != self.dev.config {
+            self.dev.config = config;
+            self.dev.config_dirty = true;
+        }
+    }
+}
+
+impl<'a, I2C, MODE, UNIT> Deref for ConfigGuard<'a, I2C, MODE, UNIT> {
+    type Target = Tmp1x2<I2C, MODE, UNIT>;
+
+    fn deref(&self) -> &Self::Target {
+        self.dev
+    }
+}
+
+impl<'a, I2C, MODE, UNIT> DerefMut for ConfigGuard<'a, I2C, MODE, UNIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.dev
+    }
+}
+
+impl<I2C, E, MODE, UNIT> Tmp1x2<I2C, MODE, UNIT>
+where
+    I2C: i2c::I2c<Error = E>,
+{
+    /// Capture the current configuration so that it can be restored later.
+    ///
+    /// The returned guard gives access to the driver in order to change the
+    /// configuration temporarily, for example to increase the conversion
+    /// rate. See [`ConfigGuard`](struct.ConfigGuard.html).
+    pub fn scoped_config(&mut self) -> ConfigGuard<'_, I2C, MODE, UNIT> {
+        let saved = self.config;
+        ConfigGuard { dev: self, saved }
+    }
+}
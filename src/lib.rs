@@ -9,13 +9,16 @@
 //! - Read the temperature.
 //! - Enable/disable the extended measurement mode.
 //! - Trigger a one-shot measurement.
-//! - Read whether the one-shot measurement result is ready.
+//! - Read whether a conversion result is ready.
 //! - Set the conversion rate.
 //! - Set the high/low temperature threshold.
 //! - Set the fault queue.
 //! - Set the alert polarity.
 //! - Set the thermostat mode.
 //! - Read whether a comparator mode alert is active.
+//! - Read the temperature of several devices on the same bus at once.
+//! - Average the temperature over several readings.
+//! - Control heating or cooling with a software thermostat.
 //!
 //! ## The devices
 //!
@@ -40,6 +43,19 @@
 //! The devices feature SMBus(TM), two-wire and I2C interface compatibility,
 //! and allows up to four devices on one bus.
 //!
+//! The devices support I²C bus clock frequencies of up to 3.4 MHz (high-speed
+//! mode). This driver cannot control the bus speed, but every register write
+//! is issued as a single I²C transaction so that it is not split across
+//! high-speed mode sequences. See [`max_bus_speed_hz()`] and
+//! [`debug_assert_bus_speed()`].
+//!
+//! The number of bytes each operation transfers can be obtained with
+//! [`transfer_size()`] to account for the bus time.
+//!
+//! [`max_bus_speed_hz()`]: fn.max_bus_speed_hz.html
+//! [`debug_assert_bus_speed()`]: fn.debug_assert_bus_speed.html
+//! [`transfer_size()`]: fn.transfer_size.html
+//!
 //! Datasheets:
 //! - [TMP102](http://www.ti.com/lit/ds/symlink/tmp102.pdf)
 //! - [TMP112x](http://www.ti.com/lit/ds/symlink/tmp112.pdf)
@@ -79,13 +95,17 @@
 //!
 //! ```no_run
 //! use linux_embedded_hal::I2cdev;
-//! use nb::block;
-//! use tmp1x2::{Tmp1x2, SlaveAddr};
+//! use tmp1x2::{Error, Tmp1x2, SlaveAddr};
 //!
 //! let dev = I2cdev::new("/dev/i2c-1").unwrap();
 //! let sensor = Tmp1x2::new(dev, SlaveAddr::default());
 //! let mut sensor = sensor.into_one_shot().ok().expect("Mode change error");
-//! let temperature = block!(sensor.read_temperature());
+//! let temperature = loop {
+//!     match sensor.read_temperature() {
+//!         Err(Error::NotReady) => continue,
+//!         result => break result.unwrap(),
+//!     }
+//! };
 //! ```
 //!
 //! ### Get the device back if there was an error during a mode change
@@ -188,12 +208,79 @@
 //! let mut sensor = Tmp1x2::new(dev, SlaveAddr::default());
 //! let alert = sensor.is_comparator_mode_alert_active().unwrap();
 //! ```
+//!
+//! ## Tracing the I²C transactions
+//!
+//! For bring-up and debugging, enabling the `trace` feature logs every I²C
+//! transaction with the device address and the bytes sent and received at
+//! `trace` level through the [`log`] crate. If the `defmt` feature is enabled
+//! as well, [`defmt`] is used instead. Without the `trace` feature, no code is
+//! generated for this.
+//!
+//! [`log`]: https://crates.io/crates/log
+//! [`defmt`]: https://crates.io/crates/defmt
+//!
+//! ## Blocking interface
+//!
+//! A blocking driver is available in the [`blocking`] module when enabling
+//! the `eh02` feature for `embedded-hal` 0.2 I²C traits or the `eh1` feature
+//! for `embedded-hal` 1.0 I²C traits. Only one of them can be enabled.
+//!
+//! [`blocking`]: blocking/index.html
+//!
+//! ## SMBus packet error checking
+//!
+//! When the `pec` feature is enabled, all register reads request an
+//! additional SMBus packet error code (PEC) byte from the device and validate
+//! it against the CRC-8 of the whole transaction. `Error::Pec` is returned on
+//! mismatch. This improves reliability on noisy buses.
+//!
+//! ## Bus timeouts
+//!
+//! The devices do not stretch the I²C clock, but bus glitches can still
+//! cause an I²C peripheral to time out. The `embedded-hal` error kinds do not
+//! cover timeouts, so these are returned as `Error::I2C` with the error of
+//! the HAL. If the HAL error implements the [`BusTimeout`] trait, timeouts
+//! can be converted into `Error::Timeout` with
+//! [`Error::detect_timeout()`](enum.Error.html#method.detect_timeout).
+//!
+//! [`BusTimeout`]: trait.BusTimeout.html
+//!
+//! ## Cancellation
+//!
+//! The futures returned by this driver can be dropped before completing,
+//! for example when losing a `select!`. The register pointer cached by the
+//! driver is forgotten before each transfer, so the next access always
+//! sends the pointer again and reads the intended register. If a one-shot
+//! temperature read is dropped while triggering the measurement, the next
+//! read triggers it again. Once triggered, the measurement is reported by
+//! the next read completing.
+//!
+//! However, a configuration or threshold write dropped while in progress
+//! may or may not have reached the device and the cached values are not
+//! updated. Repeat the write or call
+//! [`reset_internal_driver_state()`](struct.Tmp1x2.html#method.reset_internal_driver_state)
+//! after resetting the device in this case. Mode changes consume the driver
+//! and cannot be resumed when dropped.
+//!
+//! ## Recording the I²C transactions in tests
+//!
+//! When enabling the `test-util` feature, the [`RecordingWrapper`] can be
+//! put between the I²C bus and this driver to record all transactions. This
+//! allows integration tests of code using this driver to assert the exact
+//! bus traffic.
+//!
+//! [`RecordingWrapper`]: struct.RecordingWrapper.html
 
 #![deny(unsafe_code)]
 #![deny(missing_docs)]
 #![doc(html_root_url = "https://docs.rs/tmp1x2/0.2.1")]
 #![no_std]
+#![feature(pin_macro)]
+#![cfg_attr(feature = "test-util", feature(async_fn_in_trait))]
+#![cfg_attr(feature = "test-util", allow(incomplete_features))]
 
+use core::convert::TryFrom;
 use core::marker::PhantomData;
 use embedded_hal_async::i2c;
 
@@ -202,6 +289,48 @@ use embedded_hal_async::i2c;
 pub enum Error<E> {
     /// I²C bus error
     I2C(E),
+    /// SMBus packet error code (PEC) mismatch
+    ///
+    /// Only returned when the `pec` feature is enabled.
+    Pec,
+    /// The bits that always read as a fixed value did not match
+    ///
+    /// The device is probably not a TMP102 or TMP112.
+    UnexpectedDevice,
+    /// The one-shot measurement result is not available yet
+    NotReady,
+    /// Invalid input data provided
+    InvalidInputData,
+    /// The timeout elapsed before the operation completed
+    Timeout,
+}
+
+/// Detection of bus timeouts in I²C errors.
+///
+/// Implement this for the error type of an I²C peripheral able to report
+/// bus timeouts.
+pub trait BusTimeout {
+    /// Whether the error was caused by a bus timeout.
+    fn is_timeout(&self) -> bool;
+}
+
+impl<E: BusTimeout> Error<E> {
+    /// Convert an I²C bus timeout into `Error::Timeout`.
+    ///
+    /// Other errors are returned unchanged.
+    pub fn detect_timeout(self) -> Self {
+        match self {
+            Error::I2C(e) if e.is_timeout() => Error::Timeout,
+            other => other,
+        }
+    }
+}
+
+impl<E: BusTimeout> From<E> for Error<E> {
+    /// Convert an I²C error, mapping bus timeouts to `Error::Timeout`.
+    fn from(error: E) -> Self {
+        Error::I2C(error).detect_timeout()
+    }
 }
 
 /// Error type for mode changes.
@@ -216,6 +345,37 @@ pub enum ModeChangeError<E, DEV> {
     I2C(E, DEV),
 }
 
+impl<E, DEV> From<ModeChangeError<E, DEV>> for Error<E> {
+    /// Convert a mode change error into a plain error, dropping the device.
+    fn from(error: ModeChangeError<E, DEV>) -> Self {
+        match error {
+            ModeChangeError::I2C(e, _) => Error::I2C(e),
+        }
+    }
+}
+
+/// Error waiting for an unmasked alert.
+///
+/// See [`wait_for_unmasked_alert()`](struct.Tmp1x2.html#method.wait_for_unmasked_alert).
+#[derive(Debug)]
+pub enum AlertWaitError<E, PE> {
+    /// Error reading the temperature from the device
+    Device(Error<E>),
+    /// Error waiting for the ALERT pin
+    Pin(PE),
+}
+
+/// Error parsing a configuration word.
+///
+/// See [`Config::parse_hex()`](struct.Config.html#method.parse_hex).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseError {
+    /// The word does not have exactly 4 hexadecimal digits
+    InvalidLength,
+    /// The word contains a character that is not a hexadecimal digit
+    InvalidDigit,
+}
+
 /// Conversion rate for continuous conversion mode
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ConversionRate {
@@ -229,6 +389,73 @@ pub enum ConversionRate {
     _8Hz,
 }
 
+impl ConversionRate {
+    /// All conversion rates.
+    pub const fn all() -> [ConversionRate; 4] {
+        [
+            ConversionRate::_0_25Hz,
+            ConversionRate::_1Hz,
+            ConversionRate::_4Hz,
+            ConversionRate::_8Hz,
+        ]
+    }
+
+    /// Time between two conversions in milliseconds.
+    fn period_ms(self) -> u64 {
+        match self {
+            ConversionRate::_0_25Hz => 4000,
+            ConversionRate::_1Hz => 1000,
+            ConversionRate::_4Hz => 250,
+            ConversionRate::_8Hz => 125,
+        }
+    }
+}
+
+/// One-shot conversion time to wait for before polling the result
+///
+/// Waiting for the typical conversion time reduces the latency of most
+/// measurements at the cost of additional polls when the conversion takes
+/// longer. Waiting for the maximum conversion time usually needs a single
+/// poll but adds latency.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConversionTiming {
+    /// Typical conversion time (26ms)
+    Typical,
+    /// Maximum conversion time (35ms)
+    Max,
+}
+
+impl ConversionTiming {
+    /// Conversion time in milliseconds.
+    const fn ms(self) -> u32 {
+        match self {
+            ConversionTiming::Typical => 26,
+            ConversionTiming::Max => 35,
+        }
+    }
+}
+
+/// Temperature unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TempUnit {
+    /// Degrees Celsius (°C)
+    Celsius,
+    /// Degrees Fahrenheit (°F)
+    Fahrenheit,
+    /// Kelvin (K)
+    Kelvin,
+}
+
+impl TempUnit {
+    fn convert_celsius(self, celsius: f32) -> f32 {
+        match self {
+            TempUnit::Celsius => celsius,
+            TempUnit::Fahrenheit => celsius * 1.8 + 32.0,
+            TempUnit::Kelvin => celsius + 273.15,
+        }
+    }
+}
+
 /// Fault queue
 ///
 /// Number of consecutive faults necessary to trigger an alert.
@@ -244,6 +471,42 @@ pub enum FaultQueue {
     _6,
 }
 
+impl FaultQueue {
+    /// All fault queue settings.
+    pub const fn all() -> [FaultQueue; 4] {
+        [
+            FaultQueue::_1,
+            FaultQueue::_2,
+            FaultQueue::_4,
+            FaultQueue::_6,
+        ]
+    }
+
+    /// Number of consecutive faults needed to assert an alert.
+    fn depth(self) -> u8 {
+        match self {
+            FaultQueue::_1 => 1,
+            FaultQueue::_2 => 2,
+            FaultQueue::_4 => 4,
+            FaultQueue::_6 => 6,
+        }
+    }
+}
+
+/// Get the smallest fault queue filtering out spurious faults.
+///
+/// An alert is only triggered if the number of consecutive faults exceeds
+/// `expected_noise_samples`. If more than 5 spurious faults in a row are
+/// expected, the deepest queue `FaultQueue::_6` is returned, which cannot
+/// filter all of them.
+pub fn recommend_fault_queue(expected_noise_samples: u8) -> FaultQueue {
+    FaultQueue::all()
+        .iter()
+        .copied()
+        .find(|fq| fq.depth() > expected_noise_samples)
+        .unwrap_or(FaultQueue::_6)
+}
+
 /// Alert polarity
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AlertPolarity {
@@ -253,6 +516,23 @@ pub enum AlertPolarity {
     ActiveHigh,
 }
 
+impl AlertPolarity {
+    /// All alert polarities.
+    pub const fn all() -> [AlertPolarity; 2] {
+        [AlertPolarity::ActiveLow, AlertPolarity::ActiveHigh]
+    }
+
+    /// Get the ALERT pin level indicating an active alert.
+    ///
+    /// Returns `true` for a high level and `false` for a low level.
+    pub const fn active_level(&self) -> bool {
+        match self {
+            AlertPolarity::ActiveLow => false,
+            AlertPolarity::ActiveHigh => true,
+        }
+    }
+}
+
 /// Thermostat mode
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ThermostatMode {
@@ -273,6 +553,209 @@ pub enum ThermostatMode {
     Interrupt,
 }
 
+impl ThermostatMode {
+    /// All thermostat modes.
+    pub const fn all() -> [ThermostatMode; 2] {
+        [ThermostatMode::Comparator, ThermostatMode::Interrupt]
+    }
+}
+
+/// Field of the configuration register that can be changed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigField {
+    /// Conversion rate (`CR1:CR0`)
+    ConversionRate,
+    /// Extended measurement mode (`EM`)
+    ExtendedMode,
+    /// Fault queue (`F1:F0`)
+    FaultQueue,
+    /// Alert polarity (`POL`)
+    AlertPolarity,
+    /// Thermostat mode (`TM`)
+    ThermostatMode,
+}
+
+impl ConfigField {
+    /// All configuration fields.
+    pub const fn all() -> [ConfigField; 5] {
+        [
+            ConfigField::ConversionRate,
+            ConfigField::ExtendedMode,
+            ConfigField::FaultQueue,
+            ConfigField::AlertPolarity,
+            ConfigField::ThermostatMode,
+        ]
+    }
+
+    const fn mask(self) -> u16 {
+        match self {
+            ConfigField::ConversionRate => Config::CONV_RATE0 | Config::CONV_RATE1,
+            ConfigField::ExtendedMode => Config::EXTENDED_MODE,
+            ConfigField::FaultQueue => Config::FAULT_QUEUE0 | Config::FAULT_QUEUE1,
+            ConfigField::AlertPolarity => Config::ALERT_POLARITY,
+            ConfigField::ThermostatMode => Config::THERMOSTAT,
+        }
+    }
+}
+
+/// Change of a configuration field
+///
+/// See [`preview_config_after()`](struct.Tmp1x2.html#method.preview_config_after).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigChange {
+    /// Set the conversion rate
+    ConversionRate(ConversionRate),
+    /// Enable or disable the extended measurement mode
+    ExtendedMode(bool),
+    /// Set the fault queue
+    FaultQueue(FaultQueue),
+    /// Set the alert polarity
+    AlertPolarity(AlertPolarity),
+    /// Set the thermostat mode
+    ThermostatMode(ThermostatMode),
+}
+
+/// Whether changing a configuration field should be done while shut down.
+///
+/// Changing the extended mode (`EM`) while a conversion is in progress may
+/// produce a sample in the previous data format, since the temperature
+/// register format changes with it. The other fields take effect
+/// immediately or with the next conversion and can be changed in
+/// continuous conversion mode.
+///
+/// See [`set_safe_config_changes()`](struct.Tmp1x2.html#method.set_safe_config_changes).
+pub const fn requires_shutdown(field: ConfigField) -> bool {
+    matches!(field, ConfigField::ExtendedMode)
+}
+
+/// Error when decoding a configuration field from an invalid bit pattern.
+///
+/// Contains the value that could not be decoded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidBitPatternError(pub u8);
+
+impl TryFrom<u8> for ConversionRate {
+    type Error = InvalidBitPatternError;
+
+    /// Decode the conversion rate from the `CR1:CR0` bits (`0b00` to `0b11`).
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        match bits {
+            0b00 => Ok(ConversionRate::_0_25Hz),
+            0b01 => Ok(ConversionRate::_1Hz),
+            0b10 => Ok(ConversionRate::_4Hz),
+            0b11 => Ok(ConversionRate::_8Hz),
+            _ => Err(InvalidBitPatternError(bits)),
+        }
+    }
+}
+
+impl TryFrom<u8> for FaultQueue {
+    type Error = InvalidBitPatternError;
+
+    /// Decode the fault queue from the `F1:F0` bits (`0b00` to `0b11`).
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        match bits {
+            0b00 => Ok(FaultQueue::_1),
+            0b01 => Ok(FaultQueue::_2),
+            0b10 => Ok(FaultQueue::_4),
+            0b11 => Ok(FaultQueue::_6),
+            _ => Err(InvalidBitPatternError(bits)),
+        }
+    }
+}
+
+impl TryFrom<u8> for AlertPolarity {
+    type Error = InvalidBitPatternError;
+
+    /// Decode the alert polarity from the `POL` bit (`0` or `1`).
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        match bits {
+            0 => Ok(AlertPolarity::ActiveLow),
+            1 => Ok(AlertPolarity::ActiveHigh),
+            _ => Err(InvalidBitPatternError(bits)),
+        }
+    }
+}
+
+impl TryFrom<u8> for ThermostatMode {
+    type Error = InvalidBitPatternError;
+
+    /// Decode the thermostat mode from the `TM` bit (`0` or `1`).
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        match bits {
+            0 => Ok(ThermostatMode::Comparator),
+            1 => Ok(ThermostatMode::Interrupt),
+            _ => Err(InvalidBitPatternError(bits)),
+        }
+    }
+}
+
+/// Conversion mode as read from the device
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Continuous conversion mode
+    Continuous,
+    /// One-shot conversion mode (shutdown)
+    OneShot,
+}
+
+/// Device kind as detected by probing the device
+///
+/// The TMP102 and TMP112 have the same register map and behavior, so they
+/// cannot be told apart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceKind {
+    /// TMP102 or TMP112 device
+    Tmp1x2,
+    /// The device does not behave like a TMP102 or TMP112
+    Unknown,
+}
+
+/// Sampling state for reading only new temperature conversions.
+///
+/// See [`read_temperature_if_new()`](struct.Tmp1x2.html#method.read_temperature_if_new).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SampleState {
+    last_read_ms: Option<u64>,
+}
+
+/// Contents of all device registers.
+///
+/// See [`dump()`](struct.Tmp1x2.html#method.dump).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegisterDump {
+    /// Raw temperature register contents
+    pub temperature_raw: u16,
+    /// Raw configuration register contents
+    pub config_raw: u16,
+    /// Raw low temperature threshold register contents
+    pub t_low_raw: u16,
+    /// Raw high temperature threshold register contents
+    pub t_high_raw: u16,
+    /// Temperature in °C, without calibration offset
+    pub temperature: f32,
+    /// Decoded configuration
+    pub config: Config,
+    /// Low temperature threshold in °C
+    pub t_low: f32,
+    /// High temperature threshold in °C
+    pub t_high: f32,
+}
+
+/// Snapshot of the device configuration and temperature thresholds.
+///
+/// See [`save_state()`](struct.Tmp1x2.html#method.save_state) and
+/// [`restore_state()`](struct.Tmp1x2.html#method.restore_state).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeviceState {
+    /// Configuration
+    pub config: Config,
+    /// Raw low temperature threshold register contents
+    pub t_low_raw: u16,
+    /// Raw high temperature threshold register contents
+    pub t_high_raw: u16,
+}
+
 /// Possible slave addresses
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SlaveAddr {
@@ -280,6 +763,23 @@ pub enum SlaveAddr {
     Default,
     /// Alternative slave address providing bit values for A1 and A0
     Alternative(bool, bool),
+    /// Exact 7-bit slave address, for example behind an address translator
+    ///
+    /// Create it with [`SlaveAddr::exact()`].
+    Exact(ExactAddress),
+}
+
+/// Validated exact 7-bit slave address
+///
+/// Obtained through [`SlaveAddr::exact()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExactAddress(u8);
+
+impl ExactAddress {
+    /// 7-bit address value
+    pub const fn value(self) -> u8 {
+        self.0
+    }
 }
 
 impl Default for SlaveAddr {
@@ -290,23 +790,178 @@ impl Default for SlaveAddr {
 }
 
 impl SlaveAddr {
-    fn addr(self, default: u8) -> u8 {
-        match self {
+    /// All possible slave addresses
+    ///
+    /// This returns the four A1/A0 combinations in ascending address order,
+    /// which is useful for scanning the bus for devices.
+    pub const fn all() -> [SlaveAddr; 4] {
+        [
+            SlaveAddr::Alternative(false, false),
+            SlaveAddr::Alternative(false, true),
+            SlaveAddr::Alternative(true, false),
+            SlaveAddr::Alternative(true, true),
+        ]
+    }
+
+    /// Exact 7-bit slave address
+    ///
+    /// Returns `None` if `address` is not a 7-bit value.
+    pub const fn exact(address: u8) -> Option<SlaveAddr> {
+        if address <= 0x7F {
+            Some(SlaveAddr::Exact(ExactAddress(address)))
+        } else {
+            None
+        }
+    }
+
+    fn addr(&self, default: u8) -> u8 {
+        match *self {
             SlaveAddr::Default => default,
             SlaveAddr::Alternative(a1, a0) => default | ((a1 as u8) << 1) | a0 as u8,
+            SlaveAddr::Exact(address) => address.value(),
         }
     }
 }
 
 const DEVICE_BASE_ADDRESS: u8 = 0b100_1000;
 
-struct Register;
+// power-up threshold register contents (low: 75°C, high: 80°C)
+const DEFAULT_THRESHOLDS_RAW: (u16, u16) = (0x4B00, 0x5000);
+
+/// Minimum temperature in normal measurement mode in °C.
+pub const NORMAL_MIN_C: f32 = -128.0;
+/// Maximum temperature in normal measurement mode in °C.
+pub const NORMAL_MAX_C: f32 = 127.9375;
+/// Minimum temperature in extended measurement mode in °C.
+pub const EXTENDED_MIN_C: f32 = -256.0;
+/// Maximum temperature in extended measurement mode in °C.
+pub const EXTENDED_MAX_C: f32 = 255.9375;
+
+/// Error when a temperature is out of the range of a measurement mode.
+///
+/// Contains the temperature in °C that is out of range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutOfRangeError(pub f32);
 
-impl Register {
-    const TEMPERATURE: u8 = 0x00;
-    const CONFIG: u8 = 0x01;
-    const T_LOW: u8 = 0x02;
-    const T_HIGH: u8 = 0x03;
+impl<E> From<OutOfRangeError> for Error<E> {
+    /// Convert into `Error::InvalidInputData`.
+    fn from(_: OutOfRangeError) -> Self {
+        Error::InvalidInputData
+    }
+}
+
+/// Temperature in °C within the range of the normal measurement mode.
+///
+/// This is accepted by
+/// [`set_high_temperature_threshold_normal()`](struct.Tmp1x2.html#method.set_high_temperature_threshold_normal)
+/// and
+/// [`set_low_temperature_threshold_normal()`](struct.Tmp1x2.html#method.set_low_temperature_threshold_normal).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalTemp(f32);
+
+impl NormalTemp {
+    /// Create a temperature in `[NORMAL_MIN_C, NORMAL_MAX_C]`.
+    pub fn new(celsius: f32) -> Result<Self, OutOfRangeError> {
+        if (NORMAL_MIN_C..=NORMAL_MAX_C).contains(&celsius) {
+            Ok(NormalTemp(celsius))
+        } else {
+            Err(OutOfRangeError(celsius))
+        }
+    }
+
+    /// Get the temperature in °C.
+    pub fn celsius(self) -> f32 {
+        self.0
+    }
+}
+
+/// Temperature in °C within the range of the extended measurement mode.
+///
+/// This is accepted by
+/// [`set_high_temperature_threshold_extended()`](struct.Tmp1x2.html#method.set_high_temperature_threshold_extended)
+/// and
+/// [`set_low_temperature_threshold_extended()`](struct.Tmp1x2.html#method.set_low_temperature_threshold_extended).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtendedTemp(f32);
+
+impl ExtendedTemp {
+    /// Create a temperature in `[EXTENDED_MIN_C, EXTENDED_MAX_C]`.
+    pub fn new(celsius: f32) -> Result<Self, OutOfRangeError> {
+        if (EXTENDED_MIN_C..=EXTENDED_MAX_C).contains(&celsius) {
+            Ok(ExtendedTemp(celsius))
+        } else {
+            Err(OutOfRangeError(celsius))
+        }
+    }
+
+    /// Get the temperature in °C.
+    pub fn celsius(self) -> f32 {
+        self.0
+    }
+}
+
+/// Maximum I²C bus clock frequency supported by the devices in Hz.
+///
+/// This corresponds to the high-speed mode (3.4 MHz).
+pub const fn max_bus_speed_hz() -> u32 {
+    3_400_000
+}
+
+/// Assert in debug builds that the given I²C bus clock frequency in Hz is
+/// supported by the devices.
+pub fn debug_assert_bus_speed(bus_speed_hz: u32) {
+    debug_assert!(
+        bus_speed_hz <= max_bus_speed_hz(),
+        "I2C bus speed not supported by TMP1x2 devices"
+    );
+}
+
+/// Bus operation performed by this driver
+///
+/// See [`transfer_size()`](fn.transfer_size.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    /// Register read sending the register pointer first, for example a
+    /// temperature read
+    ReadRegister,
+    /// Register read without sending the register pointer, for example
+    /// consecutive reads of the same register
+    ReadCurrentRegister,
+    /// Register write, for example a configuration write
+    WriteRegister,
+    /// Register pointer write without data
+    SetPointer,
+}
+
+/// Number of bytes an operation transfers on the I²C bus.
+///
+/// This counts the data bytes written and read, excluding the address byte
+/// of each transfer. The other byte counts of this crate, like
+/// [`bus_bytes_per_second()`](struct.Tmp1x2.html#method.bus_bytes_per_second),
+/// are based on this. With the `pec` feature, reads sending the register
+/// pointer include the packet error code byte.
+pub const fn transfer_size(op: Operation) -> usize {
+    match op {
+        Operation::ReadRegister if cfg!(feature = "pec") => 4,
+        Operation::ReadRegister => 3,
+        Operation::ReadCurrentRegister => 2,
+        Operation::WriteRegister => 3,
+        Operation::SetPointer => 1,
+    }
+}
+
+/// Device registers
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum Register {
+    /// Temperature register
+    Temperature = 0x00,
+    /// Configuration register
+    Config = 0x01,
+    /// Low temperature threshold register
+    TLow = 0x02,
+    /// High temperature threshold register
+    THigh = 0x03,
 }
 
 struct BitFlagsLow;
@@ -330,12 +985,148 @@ impl BitFlagsHigh {
     const CONV_RATE1: u8 = 0b1000_0000;
 }
 
-#[derive(Debug, Clone)]
-struct Config {
+/// Configuration register contents as cached by the driver.
+///
+/// This can be obtained from a driver instance with
+/// [`into_parts()`](struct.Tmp1x2.html#method.into_parts) and used to create
+/// a new instance with the same settings with
+/// [`new_with_config()`](struct.Tmp1x2.html#method.new_with_config).
+///
+/// The raw register contents are available through [`bits()`](#method.bits)
+/// as a 16-bit value with the byte sent first by the device in the upper
+/// half, and can be inspected with the flag constants.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
     lsb: u8,
     msb: u8,
 }
 
+impl Config {
+    /// Shutdown mode (`SD`) flag.
+    pub const SHUTDOWN: u16 = BitFlagsLow::SHUTDOWN as u16;
+    /// Thermostat interrupt mode (`TM`) flag.
+    pub const THERMOSTAT: u16 = BitFlagsLow::THERMOSTAT as u16;
+    /// Alert polarity active high (`POL`) flag.
+    pub const ALERT_POLARITY: u16 = BitFlagsLow::ALERT_POLARITY as u16;
+    /// Fault queue (`F0`) flag.
+    pub const FAULT_QUEUE0: u16 = BitFlagsLow::FAULT_QUEUE0 as u16;
+    /// Fault queue (`F1`) flag.
+    pub const FAULT_QUEUE1: u16 = BitFlagsLow::FAULT_QUEUE1 as u16;
+    /// Converter resolution (`R1:R0`) flags. These are read-only.
+    pub const RESOLUTION: u16 = BitFlagsLow::RESOLUTION as u16;
+    /// One-shot/conversion ready (`OS`) flag.
+    pub const ONE_SHOT: u16 = BitFlagsLow::ONE_SHOT as u16;
+    /// Extended measurement mode (`EM`) flag.
+    pub const EXTENDED_MODE: u16 = (BitFlagsHigh::EXTENDED_MODE as u16) << 8;
+    /// Alert status (`AL`) flag. This is read-only.
+    pub const ALERT: u16 = (BitFlagsHigh::ALERT as u16) << 8;
+    /// Conversion rate (`CR0`) flag.
+    pub const CONV_RATE0: u16 = (BitFlagsHigh::CONV_RATE0 as u16) << 8;
+    /// Conversion rate (`CR1`) flag.
+    pub const CONV_RATE1: u16 = (BitFlagsHigh::CONV_RATE1 as u16) << 8;
+
+    /// Create a configuration from the raw register contents.
+    pub const fn from_bits(bits: u16) -> Self {
+        Config {
+            lsb: bits as u8,
+            msb: (bits >> 8) as u8,
+        }
+    }
+
+    /// Get the raw register contents.
+    pub const fn bits(&self) -> u16 {
+        ((self.msb as u16) << 8) | self.lsb as u16
+    }
+
+    /// Create a configuration from the register bytes in the order they are
+    /// sent on the bus.
+    pub const fn from_bytes(msb: u8, lsb: u8) -> Self {
+        Config { lsb, msb }
+    }
+
+    /// Get the register bytes in the order they are sent on the bus, that
+    /// is, most significant byte first.
+    ///
+    /// This is the data following the register pointer when writing the
+    /// configuration register.
+    pub const fn to_bytes(&self) -> [u8; 2] {
+        [self.msb, self.lsb]
+    }
+
+    /// Parse a configuration word from its hexadecimal representation.
+    ///
+    /// The word must have exactly 4 hexadecimal digits, optionally prefixed
+    /// with `0x`, in the same layout as returned by [`bits()`](#method.bits).
+    /// For example, the power-up configuration is `"A060"`.
+    pub fn parse_hex(s: &str) -> Result<Self, ParseError> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        if digits.len() != 4 {
+            return Err(ParseError::InvalidLength);
+        }
+        if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ParseError::InvalidDigit);
+        }
+        u16::from_str_radix(digits, 16)
+            .map(Config::from_bits)
+            .map_err(|_| ParseError::InvalidDigit)
+    }
+
+    /// Check whether all the given flags are set.
+    pub const fn contains(&self, flags: u16) -> bool {
+        (self.bits() & flags) == flags
+    }
+
+    /// Get whether the extended measurement mode is enabled.
+    pub fn is_extended_mode(&self) -> bool {
+        (self.msb & BitFlagsHigh::EXTENDED_MODE) != 0
+    }
+
+    /// Get the conversion rate.
+    pub fn conversion_rate(&self) -> ConversionRate {
+        let cr1 = (self.msb & BitFlagsHigh::CONV_RATE1) != 0;
+        let cr0 = (self.msb & BitFlagsHigh::CONV_RATE0) != 0;
+        match (cr1, cr0) {
+            (false, false) => ConversionRate::_0_25Hz,
+            (false, true) => ConversionRate::_1Hz,
+            (true, false) => ConversionRate::_4Hz,
+            (true, true) => ConversionRate::_8Hz,
+        }
+    }
+
+    /// Get the fault queue.
+    pub fn fault_queue(&self) -> FaultQueue {
+        let f1 = (self.lsb & BitFlagsLow::FAULT_QUEUE1) != 0;
+        let f0 = (self.lsb & BitFlagsLow::FAULT_QUEUE0) != 0;
+        match (f1, f0) {
+            (false, false) => FaultQueue::_1,
+            (false, true) => FaultQueue::_2,
+            (true, false) => FaultQueue::_4,
+            (true, true) => FaultQueue::_6,
+        }
+    }
+
+    /// Get the alert polarity.
+    pub fn alert_polarity(&self) -> AlertPolarity {
+        if (self.lsb & BitFlagsLow::ALERT_POLARITY) != 0 {
+            AlertPolarity::ActiveHigh
+        } else {
+            AlertPolarity::ActiveLow
+        }
+    }
+
+    /// Get the thermostat mode.
+    pub fn thermostat_mode(&self) -> ThermostatMode {
+        if (self.lsb & BitFlagsLow::THERMOSTAT) != 0 {
+            ThermostatMode::Interrupt
+        } else {
+            ThermostatMode::Comparator
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config {
@@ -345,6 +1136,42 @@ impl Default for Config {
     }
 }
 
+/// Complete desired device configuration.
+///
+/// See [`apply()`](struct.Tmp1x2.html#method.apply). The default values
+/// correspond to the power-up state of the device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DesiredConfig {
+    /// Conversion rate
+    pub conversion_rate: ConversionRate,
+    /// Fault queue
+    pub fault_queue: FaultQueue,
+    /// Alert polarity
+    pub alert_polarity: AlertPolarity,
+    /// Thermostat mode
+    pub thermostat_mode: ThermostatMode,
+    /// Whether the extended measurement mode is enabled
+    pub extended_mode: bool,
+    /// Low temperature threshold in °C
+    pub low_threshold: f32,
+    /// High temperature threshold in °C
+    pub high_threshold: f32,
+}
+
+impl Default for DesiredConfig {
+    fn default() -> Self {
+        DesiredConfig {
+            conversion_rate: ConversionRate::_4Hz,
+            fault_queue: FaultQueue::_1,
+            alert_polarity: AlertPolarity::ActiveLow,
+            thermostat_mode: ThermostatMode::Comparator,
+            extended_mode: false,
+            low_threshold: 75.0,
+            high_threshold: 80.0,
+        }
+    }
+}
+
 #[doc(hidden)]
 pub mod marker {
     pub mod mode {
@@ -353,11 +1180,45 @@ pub mod marker {
         #[derive(Debug)]
         pub struct OneShot(());
     }
+
+    pub mod unit {
+        use crate::TempUnit;
+
+        /// Unit of the temperature readings of a driver instance.
+        pub trait Unit {
+            /// The unit.
+            const UNIT: TempUnit;
+        }
+
+        #[derive(Debug)]
+        pub struct Celsius(());
+        #[derive(Debug)]
+        pub struct Fahrenheit(());
+        #[derive(Debug)]
+        pub struct Kelvin(());
+
+        impl Unit for Celsius {
+            const UNIT: TempUnit = TempUnit::Celsius;
+        }
+
+        impl Unit for Fahrenheit {
+            const UNIT: TempUnit = TempUnit::Fahrenheit;
+        }
+
+        impl Unit for Kelvin {
+            const UNIT: TempUnit = TempUnit::Kelvin;
+        }
+    }
 }
 
 /// TMP1X2 device driver.
+///
+/// The temperature unit returned by
+/// [`read_temperature()`](#method.read_temperature) is selected through the
+/// `UNIT` type parameter, which defaults to °C. See
+/// [`new_with_unit()`](#method.new_with_unit).
 #[derive(Debug, Default)]
-pub struct Tmp1x2<I2C, MODE> {
+pub struct Tmp1x2<I2C, MODE, UNIT = marker::unit::Celsius> {
     /// The concrete I²C device implementation.
     i2c: I2C,
     /// The I²C device address.
@@ -366,7 +1227,26 @@ pub struct Tmp1x2<I2C, MODE> {
     config: Config,
     /// A temperature conversion was started.
     a_temperature_conversion_was_started: bool,
-    _mode: PhantomData<MODE>,
+    /// Number of successful temperature reads.
+    sample_count: u32,
+    /// Calibration offset in °C added to temperature readings.
+    calibration_offset: f32,
+    /// Whether the cached configuration still has to be written to the
+    /// device.
+    config_dirty: bool,
+    /// Whether configuration writes are deferred while shut down.
+    defer_config_writes: bool,
+    /// Whether changes requiring it are bracketed by a shutdown.
+    safe_config_changes: bool,
+    /// Last temperature read with `read_temperature_delta()`.
+    last_temperature: Option<f32>,
+    /// Register the device pointer is known to be set to.
+    pointer: Option<Register>,
+    /// Threshold register contents last written (low, high).
+    thresholds_raw: (u16, u16),
+    /// Whether alerts caused by the thresholds are reported (low, high).
+    alert_enabled: (bool, bool),
+    _mode: PhantomData<(MODE, UNIT)>,
 }
 
 impl<I2C, E> Tmp1x2<I2C, marker::mode::Continuous>
@@ -377,26 +1257,126 @@ where
     ///
     /// By default they are in continuous conversion mode.
     pub fn new(i2c: I2C, address: SlaveAddr) -> Self {
+        Tmp1x2::new_with_unit(i2c, address)
+    }
+
+    /// Create new instance of the TMP102 or TMP112x device with a known
+    /// configuration.
+    ///
+    /// This does not write the configuration to the device. It is intended
+    /// to recreate a driver instance with the configuration obtained through
+    /// [`into_parts()`](#method.into_parts) from an instance in continuous
+    /// conversion mode without having to reconfigure the device.
+    ///
+    /// The shutdown bit of `config` is cleared, since the instance is in
+    /// continuous conversion mode. A device that was shut down stays shut
+    /// down until the configuration register is written next, for example
+    /// through [`write_register()`](#method.write_register).
+    pub fn new_with_config(i2c: I2C, address: SlaveAddr, config: Config) -> Self {
+        Tmp1x2::from_config(i2c, address, config.with_shutdown(false))
+    }
+}
+
+impl<I2C, E, UNIT> Tmp1x2<I2C, marker::mode::Continuous, UNIT>
+where
+    I2C: i2c::I2c<Error = E>,
+    UNIT: marker::unit::Unit,
+{
+    /// Create new instance of the TMP102 or TMP112x device reading the
+    /// temperature in the given unit.
+    ///
+    /// The unit is selected through the `UNIT` type parameter, for example
+    /// `Tmp1x2::<_, _, Fahrenheit>::new_with_unit(i2c, address)` with the
+    /// `Fahrenheit` marker of the `marker::unit` module.
+    /// [`read_temperature()`](#method.read_temperature) and the other
+    /// temperature reads then return temperatures in this unit. This
+    /// includes the helpers reading through a driver instance like `Ewma`.
+    /// Only [`read_temperature_scaled()`](#method.read_temperature_scaled),
+    /// [`read_temperature_in()`](#method.read_temperature_in),
+    /// [`read_temperature_rounded()`](#method.read_temperature_rounded) and
+    /// [`read_as()`](#method.read_as) are not affected, since they select
+    /// the unit themselves.
+    pub fn new_with_unit(i2c: I2C, address: SlaveAddr) -> Self {
+        Tmp1x2::from_config(i2c, address, Config::default())
+    }
+
+    fn from_config(i2c: I2C, address: SlaveAddr, config: Config) -> Self {
         Tmp1x2 {
             i2c,
             address: address.addr(DEVICE_BASE_ADDRESS),
-            config: Config::default(),
+            config,
             a_temperature_conversion_was_started: false,
+            sample_count: 0,
+            calibration_offset: 0.0,
+            config_dirty: false,
+            defer_config_writes: false,
+            safe_config_changes: false,
+            last_temperature: None,
+            pointer: None,
+            thresholds_raw: DEFAULT_THRESHOLDS_RAW,
+            alert_enabled: (true, true),
             _mode: PhantomData,
         }
     }
 }
 
-impl<I2C, MODE> Tmp1x2<I2C, MODE> {
+impl<I2C, MODE, UNIT> Tmp1x2<I2C, MODE, UNIT> {
     /// Destroy driver instance, return I²C bus instance.
     pub fn destroy(self) -> I2C {
         self.i2c
     }
+
+    /// Destroy driver instance, return I²C bus instance and the last known
+    /// configuration.
+    ///
+    /// The configuration can be used to recreate the driver instance later
+    /// on with [`new_with_config()`](#method.new_with_config).
+    pub fn into_parts(self) -> (I2C, Config) {
+        (self.i2c, self.config)
+    }
+
+    /// Replace the I²C bus instance, return the previous one.
+    ///
+    /// The configuration and address are kept. The caller must ensure that
+    /// the new bus reaches the same physical device, for example through an
+    /// I²C multiplexer. The register the device pointer was last set to is
+    /// forgotten, so the next register read sends the pointer again.
+    pub fn replace_bus(&mut self, new: I2C) -> I2C {
+        self.pointer = None;
+        core::mem::replace(&mut self.i2c, new)
+    }
 }
 
+#[macro_use]
+mod fmt;
+#[cfg(any(feature = "eh02", feature = "eh1"))]
+pub mod blocking;
+mod bus;
 mod configuration;
 mod conversion;
+pub use crate::conversion::{decode_temperature, FromRawTemperature};
+mod delta;
+pub use crate::delta::DeltaAlert;
+mod fault;
+pub use crate::fault::FaultTracker;
+mod filter;
+pub use crate::filter::{Averager, Ewma, MedianFilter};
+mod guard;
+pub use crate::guard::ConfigGuard;
+#[cfg(feature = "pec")]
+mod pec;
 mod reading;
+pub use crate::reading::read_all;
+#[cfg(feature = "test-util")]
+mod recording;
+#[cfg(feature = "test-util")]
+pub use crate::recording::{RecordedTransaction, RecordingWrapper};
+mod stale;
+pub use crate::stale::StaleDetector;
+mod sync;
+pub use crate::sync::ConversionSync;
+mod thermostat;
+pub use crate::thermostat::{Thermostat, ThermostatOutput};
 
 //impl<E> core::fmt::Debug for nb::Error<E> {}
 
@@ -406,6 +1386,71 @@ mod tests {
     use DEVICE_BASE_ADDRESS as BASE_ADDR;
     extern crate embedded_hal_mock as hal;
 
+    #[test]
+    fn config_bits_roundtrip() {
+        let bits =
+            Config::EXTENDED_MODE | Config::CONV_RATE0 | Config::RESOLUTION | Config::SHUTDOWN;
+        let config = Config::from_bits(bits);
+        assert_eq!(bits, config.bits());
+        assert_eq!(0b0101_0000_0110_0001, config.bits());
+        assert!(config.contains(Config::EXTENDED_MODE | Config::SHUTDOWN));
+        assert!(!config.contains(Config::ALERT));
+        assert!(config.is_extended_mode());
+        assert_eq!(ConversionRate::_1Hz, config.conversion_rate());
+    }
+
+    #[test]
+    fn config_bytes_roundtrip() {
+        let config = Config::from_bytes(0b0110_0000, 0b1010_0001);
+        assert_eq!([0b0110_0000, 0b1010_0001], config.to_bytes());
+        assert_eq!(0b0110_0000_1010_0001, config.bits());
+        let config = Config::default();
+        let [msb, lsb] = config.to_bytes();
+        assert_eq!(config, Config::from_bytes(msb, lsb));
+    }
+
+    #[test]
+    fn can_parse_config_word() {
+        assert_eq!(Ok(Config::default()), Config::parse_hex("A060"));
+        assert_eq!(Ok(Config::default()), Config::parse_hex("0xa060"));
+        assert_eq!(Ok(Config::from_bits(0x60A0)), Config::parse_hex("0X60A0"));
+    }
+
+    #[test]
+    fn cannot_parse_invalid_config_word() {
+        assert_eq!(Err(ParseError::InvalidLength), Config::parse_hex(""));
+        assert_eq!(Err(ParseError::InvalidLength), Config::parse_hex("0x"));
+        assert_eq!(Err(ParseError::InvalidLength), Config::parse_hex("A06"));
+        assert_eq!(Err(ParseError::InvalidLength), Config::parse_hex("0xA0601"));
+        assert_eq!(Err(ParseError::InvalidDigit), Config::parse_hex("A0G0"));
+        assert_eq!(Err(ParseError::InvalidDigit), Config::parse_hex("+A06"));
+    }
+
+    #[test]
+    fn default_config_bits() {
+        let config = Config::default();
+        assert_eq!(0b1010_0000_0110_0000, config.bits());
+        assert_eq!(ConversionRate::_4Hz, config.conversion_rate());
+        assert_eq!(FaultQueue::_1, config.fault_queue());
+        assert_eq!(AlertPolarity::ActiveLow, config.alert_polarity());
+        assert_eq!(ThermostatMode::Comparator, config.thermostat_mode());
+        assert!(!config.is_extended_mode());
+    }
+
+    #[test]
+    fn can_decode_config_flags() {
+        let config = Config::from_bits(
+            Config::FAULT_QUEUE1
+                | Config::FAULT_QUEUE0
+                | Config::ALERT_POLARITY
+                | Config::THERMOSTAT,
+        );
+        assert_eq!(FaultQueue::_6, config.fault_queue());
+        assert_eq!(AlertPolarity::ActiveHigh, config.alert_polarity());
+        assert_eq!(ThermostatMode::Interrupt, config.thermostat_mode());
+        assert_eq!(ConversionRate::_0_25Hz, config.conversion_rate());
+    }
+
     #[test]
     fn can_get_default_address() {
         let addr = SlaveAddr::default();
@@ -432,6 +1477,176 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_use_exact_address() {
+        assert_eq!(0x2A, SlaveAddr::exact(0x2A).unwrap().addr(BASE_ADDR));
+        assert_eq!(0x7F, SlaveAddr::exact(0x7F).unwrap().addr(BASE_ADDR));
+    }
+
+    #[test]
+    fn exact_address_must_have_7_bits() {
+        assert_eq!(None, SlaveAddr::exact(0x80));
+        assert_eq!(None, SlaveAddr::exact(0xFF));
+    }
+
+    #[test]
+    fn all_addresses_resolve_in_order() {
+        let addresses = SlaveAddr::all().map(|address| address.addr(BASE_ADDR));
+        assert_eq!([0x48, 0x49, 0x4A, 0x4B], addresses);
+    }
+
+    #[test]
+    fn alert_polarity_active_level() {
+        assert!(!AlertPolarity::ActiveLow.active_level());
+        assert!(AlertPolarity::ActiveHigh.active_level());
+    }
+
+    #[test]
+    fn can_decode_conversion_rate() {
+        assert_eq!(Ok(ConversionRate::_0_25Hz), ConversionRate::try_from(0b00));
+        assert_eq!(Ok(ConversionRate::_1Hz), ConversionRate::try_from(0b01));
+        assert_eq!(Ok(ConversionRate::_4Hz), ConversionRate::try_from(0b10));
+        assert_eq!(Ok(ConversionRate::_8Hz), ConversionRate::try_from(0b11));
+        assert_eq!(
+            Err(InvalidBitPatternError(0b100)),
+            ConversionRate::try_from(0b100)
+        );
+    }
+
+    #[test]
+    fn can_decode_fault_queue() {
+        assert_eq!(Ok(FaultQueue::_1), FaultQueue::try_from(0b00));
+        assert_eq!(Ok(FaultQueue::_2), FaultQueue::try_from(0b01));
+        assert_eq!(Ok(FaultQueue::_4), FaultQueue::try_from(0b10));
+        assert_eq!(Ok(FaultQueue::_6), FaultQueue::try_from(0b11));
+        assert_eq!(
+            Err(InvalidBitPatternError(0xFF)),
+            FaultQueue::try_from(0xFF)
+        );
+    }
+
+    #[test]
+    fn can_decode_alert_polarity() {
+        assert_eq!(Ok(AlertPolarity::ActiveLow), AlertPolarity::try_from(0));
+        assert_eq!(Ok(AlertPolarity::ActiveHigh), AlertPolarity::try_from(1));
+        assert_eq!(Err(InvalidBitPatternError(2)), AlertPolarity::try_from(2));
+    }
+
+    #[test]
+    fn can_decode_thermostat_mode() {
+        assert_eq!(Ok(ThermostatMode::Comparator), ThermostatMode::try_from(0));
+        assert_eq!(Ok(ThermostatMode::Interrupt), ThermostatMode::try_from(1));
+        assert_eq!(Err(InvalidBitPatternError(2)), ThermostatMode::try_from(2));
+    }
+
+    #[test]
+    fn can_convert_mode_change_error() {
+        let error: Error<u8> = ModeChangeError::I2C(5, ()).into();
+        match error {
+            Error::I2C(5) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn can_enumerate_all_variants() {
+        assert_eq!(
+            [
+                ConversionRate::_0_25Hz,
+                ConversionRate::_1Hz,
+                ConversionRate::_4Hz,
+                ConversionRate::_8Hz
+            ],
+            ConversionRate::all()
+        );
+        assert_eq!(
+            [
+                FaultQueue::_1,
+                FaultQueue::_2,
+                FaultQueue::_4,
+                FaultQueue::_6
+            ],
+            FaultQueue::all()
+        );
+        assert_eq!(
+            [AlertPolarity::ActiveLow, AlertPolarity::ActiveHigh],
+            AlertPolarity::all()
+        );
+        assert_eq!(
+            [ThermostatMode::Comparator, ThermostatMode::Interrupt],
+            ThermostatMode::all()
+        );
+    }
+
+    #[test]
+    fn max_bus_speed_is_high_speed_mode() {
+        assert_eq!(3_400_000, max_bus_speed_hz());
+        debug_assert_bus_speed(400_000);
+        debug_assert_bus_speed(3_400_000);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn debug_assert_bus_speed_can_fail() {
+        debug_assert_bus_speed(3_400_001);
+    }
+
+    #[test]
+    fn transfer_sizes() {
+        let read_size = if cfg!(feature = "pec") { 4 } else { 3 };
+        assert_eq!(read_size, transfer_size(Operation::ReadRegister));
+        assert_eq!(2, transfer_size(Operation::ReadCurrentRegister));
+        assert_eq!(3, transfer_size(Operation::WriteRegister));
+        assert_eq!(1, transfer_size(Operation::SetPointer));
+    }
+
+    #[test]
+    fn normal_temp_is_range_checked() {
+        assert_eq!(
+            Ok(NORMAL_MIN_C),
+            NormalTemp::new(NORMAL_MIN_C).map(NormalTemp::celsius)
+        );
+        assert_eq!(
+            Ok(NORMAL_MAX_C),
+            NormalTemp::new(NORMAL_MAX_C).map(NormalTemp::celsius)
+        );
+        assert_eq!(Err(OutOfRangeError(128.0)), NormalTemp::new(128.0));
+        assert_eq!(Err(OutOfRangeError(-128.5)), NormalTemp::new(-128.5));
+        assert!(NormalTemp::new(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn extended_temp_is_range_checked() {
+        assert_eq!(
+            Ok(200.0),
+            ExtendedTemp::new(200.0).map(ExtendedTemp::celsius)
+        );
+        assert_eq!(
+            Ok(EXTENDED_MIN_C),
+            ExtendedTemp::new(EXTENDED_MIN_C).map(ExtendedTemp::celsius)
+        );
+        assert_eq!(
+            Ok(EXTENDED_MAX_C),
+            ExtendedTemp::new(EXTENDED_MAX_C).map(ExtendedTemp::celsius)
+        );
+        assert_eq!(Err(OutOfRangeError(256.0)), ExtendedTemp::new(256.0));
+        assert_eq!(Err(OutOfRangeError(-256.5)), ExtendedTemp::new(-256.5));
+        assert!(ExtendedTemp::new(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn recommends_smallest_filtering_fault_queue() {
+        assert_eq!(FaultQueue::_1, recommend_fault_queue(0));
+        assert_eq!(FaultQueue::_2, recommend_fault_queue(1));
+        assert_eq!(FaultQueue::_4, recommend_fault_queue(2));
+        assert_eq!(FaultQueue::_4, recommend_fault_queue(3));
+        assert_eq!(FaultQueue::_6, recommend_fault_queue(4));
+        assert_eq!(FaultQueue::_6, recommend_fault_queue(5));
+        assert_eq!(FaultQueue::_6, recommend_fault_queue(6));
+        assert_eq!(FaultQueue::_6, recommend_fault_queue(255));
+    }
+
     #[test]
     fn default_config() {
         // let dev = Tmp1x2::new(hal::i2c::Mock::new(&[]), SlaveAddr::default());
@@ -0,0 +1,41 @@
+// SMBus packet error code (PEC) calculation
+
+/// CRC-8 with polynomial x^8 + x^2 + x + 1 (0x07) and initial value 0.
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            if (crc & 0x80) != 0 {
+                crc = (crc << 1) ^ 0x07;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
+
+/// PEC expected for reading the two bytes of a register.
+///
+/// This covers the whole transaction: the address with write bit, the
+/// register pointer, the address with read bit and the data bytes.
+pub fn read_pec(address: u8, register: u8, data: [u8; 2]) -> u8 {
+    crc8(&[address << 1, register, (address << 1) | 1, data[0], data[1]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc8, read_pec};
+
+    #[test]
+    fn can_calculate_crc8() {
+        assert_eq!(0xF4, crc8(b"123456789"));
+        assert_eq!(0, crc8(&[]));
+    }
+
+    #[test]
+    fn can_calculate_read_pec() {
+        assert_eq!(0xC6, read_pec(0b100_1000, 0, [0b0110_0100, 0]));
+    }
+}
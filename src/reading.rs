@@ -1,35 +1,466 @@
-use crate::conversion::convert_temp_from_register;
-use crate::{marker::mode, BitFlagsHigh, BitFlagsLow, Error, Register, Tmp1x2};
+use crate::bus::{needs_pointer, register_data, REGISTER_READ_LEN};
+use crate::conversion::{
+    convert_temp_from_register, convert_temp_from_register_raw, decode_temperature,
+    round_temperature, FromRawTemperature,
+};
+use crate::{
+    marker::{mode, unit::Unit},
+    AlertPolarity, AlertWaitError, BitFlagsHigh, BitFlagsLow, Config, ConversionTiming, Error,
+    Mode, Register, RegisterDump, SampleState, StaleDetector, TempUnit, Tmp1x2,
+};
 // use embedded_hal::blocking::i2c;
-use embedded_hal_async::i2c;
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+use embedded_hal_async::{delay::DelayUs, digital::Wait, i2c};
 
-impl<I2C, E> Tmp1x2<I2C, mode::Continuous>
+/// Interval at which the conversion-ready bit is polled in milliseconds.
+const READY_POLL_INTERVAL_MS: u32 = 5;
+
+impl<I2C, E, UNIT> Tmp1x2<I2C, mode::Continuous, UNIT>
 where
     I2C: i2c::I2c<Error = E>,
+    UNIT: Unit,
 {
     /// Read the temperature from the sensor.
+    ///
+    /// The calibration offset set with
+    /// [`set_calibration_offset()`](#method.set_calibration_offset) is added
+    /// to the result. The result is in the unit of this driver instance,
+    /// which is °C unless selected otherwise with
+    /// [`new_with_unit()`](#method.new_with_unit).
+    ///
+    /// Decoding the register contents cannot panic for any value received
+    /// from the device.
     pub async fn read_temperature(&mut self) -> Result<f32, Error<E>> {
+        let celsius = self.read_temperature_celsius().await?;
+        Ok(UNIT::UNIT.convert_celsius(celsius))
+    }
+
+    /// Read the temperature from the sensor saving the register pointer byte.
+    ///
+    /// The device keeps the register pointer after each access. If the last
+    /// access through this driver was to the temperature register, this
+    /// performs a bare 2-byte read without sending the pointer byte.
+    /// Otherwise, the pointer is sent and kept for subsequent calls.
+    ///
+    /// Every register read of this driver omits the pointer byte like this,
+    /// so this is the same as [`read_temperature()`](#method.read_temperature).
+    /// With the `pec` feature, the pointer byte is always sent.
+    pub async fn read_temperature_fast(&mut self) -> Result<f32, Error<E>> {
+        self.read_temperature().await
+    }
+
+    /// Read the temperature with separate pointer write and data read.
+    ///
+    /// This is like [`read_temperature()`](#method.read_temperature) but
+    /// writes the register pointer and reads the data in two transactions
+    /// instead of a write followed by a read with a repeated start. This
+    /// suits HALs which only support DMA for plain reads and writes, at the
+    /// cost of an additional start condition and address byte. The packet
+    /// error code is not checked with the `pec` feature.
+    pub async fn read_temperature_split(&mut self) -> Result<f32, Error<E>> {
+        self.set_pointer(Register::Temperature).await?;
         let mut data = [0; 2];
-        self.i2c
-            .write_read(self.address, &[Register::TEMPERATURE], &mut data)
-            .await
-            .map_err(Error::I2C)?;
-        Ok(convert_temp_from_register(data[0], data[1]))
+        self.read_current_register(&mut data).await?;
+        self.sample_count = self.sample_count.wrapping_add(1);
+        let celsius = convert_temp_from_register(data[0], data[1]) + self.calibration_offset;
+        Ok(UNIT::UNIT.convert_celsius(celsius))
+    }
+
+    async fn read_temperature_celsius(&mut self) -> Result<f32, Error<E>> {
+        let data = self.read_register(Register::Temperature).await?;
+        self.sample_count = self.sample_count.wrapping_add(1);
+        Ok(convert_temp_from_register(data[0], data[1]) + self.calibration_offset)
+    }
+
+    /// Read the temperature from the sensor into `out`.
+    ///
+    /// This is like [`read_temperature()`](#method.read_temperature) but
+    /// stores the result in a caller-provided variable. `out` is left
+    /// untouched on error.
+    pub async fn read_temperature_into(&mut self, out: &mut f32) -> Result<(), Error<E>> {
+        *out = self.read_temperature().await?;
+        Ok(())
+    }
+
+    /// Read the temperature from the sensor and apply a linear scale.
+    ///
+    /// This returns `celsius * scale + offset`. For example, a `scale` of
+    /// `1.8` and an `offset` of `32.0` returns the temperature in °F.
+    pub async fn read_temperature_scaled(
+        &mut self,
+        scale: f32,
+        offset: f32,
+    ) -> Result<f32, Error<E>> {
+        let celsius = self.read_temperature_celsius().await?;
+        Ok(celsius * scale + offset)
+    }
+
+    /// Read the temperature from the sensor in the given unit.
+    ///
+    /// This allows selecting the unit at runtime, for example from user
+    /// settings. The calibration offset is added in °C before the
+    /// conversion as in [`read_temperature()`](#method.read_temperature).
+    pub async fn read_temperature_in(&mut self, unit: TempUnit) -> Result<f32, Error<E>> {
+        let celsius = self.read_temperature_celsius().await?;
+        Ok(unit.convert_celsius(celsius))
+    }
+
+    /// Read the temperature from the sensor as sign and magnitude.
+    ///
+    /// Returns whether the temperature is negative and its absolute value
+    /// in the unit of this driver instance, which is convenient for displays
+    /// formatting both separately. The calibration offset is added as in
+    /// [`read_temperature()`](#method.read_temperature).
+    pub async fn read_temperature_sign_magnitude(&mut self) -> Result<(bool, f32), Error<E>> {
+        let temperature = self.read_temperature().await?;
+        let is_negative = temperature < 0.0;
+        let magnitude = if is_negative {
+            -temperature
+        } else {
+            temperature
+        };
+        Ok((is_negative, magnitude))
+    }
+
+    /// Read the temperature from the sensor in the requested type.
+    ///
+    /// The output type is selected through the type parameter: `f32` for
+    /// °C, `i16` for the raw value in units of 0.0625°C and `i32` for m°C.
+    ///
+    /// The calibration offset is not applied to the result.
+    pub async fn read_as<T: FromRawTemperature>(&mut self) -> Result<T, Error<E>> {
+        let raw = self.read_temperature_counts().await?;
+        Ok(T::from_raw_temperature(raw))
+    }
+
+    /// Read the temperature from the sensor rounded to `digits` decimal places.
+    ///
+    /// The rounding is done half away from zero using integer arithmetic,
+    /// so that for example 25.0625°C is returned as `25.1` for one decimal
+    /// place. The resolution of the devices is 0.0625°C, so values with
+    /// more than four decimal places are returned unchanged and rounding to
+    /// more than one decimal place does not add accuracy.
+    ///
+    /// The calibration offset is not applied to the result.
+    pub async fn read_temperature_rounded(&mut self, digits: u8) -> Result<f32, Error<E>> {
+        let raw = self.read_temperature_counts().await?;
+        Ok(round_temperature(raw, digits))
+    }
+
+    /// Read the temperature from the sensor as a Q12.4 fixed-point value.
+    ///
+    /// The value has 4 fractional bits, which corresponds to the resolution
+    /// of the devices of 0.0625°C. For example, 25°C is returned as `0x0190`.
+    /// This is the same value as returned by
+    /// [`read_as::<i16>()`](#method.read_as) and is valid in both normal and
+    /// extended mode.
+    ///
+    /// The calibration offset is not applied to the result.
+    pub async fn read_temperature_q12_4(&mut self) -> Result<i16, Error<E>> {
+        self.read_temperature_counts().await
+    }
+
+    /// Read the temperature from the sensor, retrying on I²C errors.
+    ///
+    /// If the read fails with an I²C error, it is retried up to `retries`
+    /// times before returning the last error. Other errors are returned
+    /// immediately.
+    ///
+    /// *NOTE*: This only helps with transient bus errors like an occasional
+    /// NACK. Persistent errors will be returned after all retries.
+    pub async fn read_temperature_retry(&mut self, retries: u8) -> Result<f32, Error<E>> {
+        let mut remaining = retries;
+        loop {
+            match self.read_temperature().await {
+                Err(Error::I2C(_)) if remaining > 0 => remaining -= 1,
+                result => return result,
+            }
+        }
+    }
+
+    /// Read the temperature and clear a pending alert.
+    ///
+    /// This is intended for handling alerts in interrupt thermostat mode.
+    /// It reads the temperature and then the configuration register, which
+    /// clears the alert. Returns the temperature and whether the alert status
+    /// bit was active, taking into account the alert polarity selected.
+    ///
+    /// *NOTE*: The alert status bit always corresponds to the activation
+    /// status as defined by the comparator mode. See
+    /// [`is_comparator_mode_alert_active()`](#method.is_comparator_mode_alert_active).
+    pub async fn read_temperature_and_clear_alert(&mut self) -> Result<(f32, bool), Error<E>> {
+        let temperature = self.read_temperature().await?;
+        let data = self.read_register(Register::Config).await?;
+        Ok((temperature, is_alert_active(data)))
+    }
+
+    /// Read the temperature decoded according to the device configuration.
+    ///
+    /// This reads the temperature and then the configuration register and
+    /// decodes the temperature according to the extended mode bit read from
+    /// the device. This avoids decoding errors if the extended mode was
+    /// changed without going through this driver, at the cost of an
+    /// additional register read. The calibration offset is added and the
+    /// result converted to the unit of this driver instance as in
+    /// [`read_temperature()`](#method.read_temperature).
+    ///
+    /// *NOTE*: In interrupt thermostat mode, reading the configuration
+    /// register clears a pending alert.
+    pub async fn read_temperature_robust(&mut self) -> Result<f32, Error<E>> {
+        let data = self.read_register(Register::Temperature).await?;
+        let config = self.read_register(Register::Config).await?;
+        self.sample_count = self.sample_count.wrapping_add(1);
+        let extended_mode = Config::from_bytes(config[0], config[1]).is_extended_mode();
+        let celsius = decode_temperature(data[0], data[1], extended_mode) + self.calibration_offset;
+        Ok(UNIT::UNIT.convert_celsius(celsius))
+    }
+
+    /// Read the temperature from the sensor together with a sample counter.
+    ///
+    /// The counter is incremented on each successful temperature read
+    /// through this driver and wraps around on overflow. Together with the
+    /// conversion rate, this allows detecting dropped or duplicate samples.
+    pub async fn read_temperature_counted(&mut self) -> Result<(f32, u32), Error<E>> {
+        let temperature = self.read_temperature().await?;
+        Ok((temperature, self.sample_count))
+    }
+
+    /// Read the temperature and the change since the previous call.
+    ///
+    /// Returns the temperature in the unit of this driver instance together
+    /// with the difference to the temperature returned by the previous call
+    /// to this method. On the first call the difference is `0.0`.
+    pub async fn read_temperature_delta(&mut self) -> Result<(f32, f32), Error<E>> {
+        let temperature = self.read_temperature().await?;
+        let delta = self.last_temperature.map_or(0.0, |last| temperature - last);
+        self.last_temperature = Some(temperature);
+        Ok((temperature, delta))
+    }
+
+    /// Read the temperature and check whether the readings became stale.
+    ///
+    /// Returns the temperature in the unit of this driver instance,
+    /// including the calibration offset, and whether `detector` considers
+    /// the readings stale, which may indicate a stuck sensor.
+    pub async fn read_temperature_with_health(
+        &mut self,
+        detector: &mut StaleDetector,
+    ) -> Result<(f32, bool), Error<E>> {
+        let raw = self.read_temperature_counts().await?;
+        let is_stale = detector.update(raw);
+        let celsius = f32::from(raw) * 0.0625 + self.calibration_offset;
+        Ok((UNIT::UNIT.convert_celsius(celsius), is_stale))
+    }
+
+    /// Read the temperature and the low and high temperature thresholds.
+    ///
+    /// Returns `(temperature, t_low, t_high)` in the unit of this driver
+    /// instance. The calibration offset is added to the temperature only.
+    /// The device does not increment the register pointer automatically, so
+    /// this takes three transactions, each sending the pointer of the
+    /// register read.
+    pub async fn read_temperature_and_thresholds(&mut self) -> Result<(f32, f32, f32), Error<E>> {
+        let temperature = self.read_temperature().await?;
+        let extended_mode = self.config.is_extended_mode();
+        let t_low = self.read_register(Register::TLow).await?;
+        let t_high = self.read_register(Register::THigh).await?;
+        Ok((
+            temperature,
+            UNIT::UNIT.convert_celsius(decode_temperature(t_low[0], t_low[1], extended_mode)),
+            UNIT::UNIT.convert_celsius(decode_temperature(t_high[0], t_high[1], extended_mode)),
+        ))
+    }
+
+    /// Read whether the temperature is within the threshold window.
+    ///
+    /// This reads the temperature and returns whether it is within
+    /// `[T_LOW, T_HIGH]`, using the thresholds last written through this
+    /// driver or the power-up values. The comparison is done on the register
+    /// values like the device does, so the calibration offset is not taken
+    /// into account. This allows alerting in software if the ALERT pin is
+    /// not connected.
+    ///
+    /// *NOTE*: In interrupt thermostat mode, reading any register of the
+    /// device clears a pending alert.
+    pub async fn is_within_window(&mut self) -> Result<bool, Error<E>> {
+        let data = self.read_register(Register::Temperature).await?;
+        self.sample_count = self.sample_count.wrapping_add(1);
+        // all registers use the same left-justified two's complement format,
+        // except for the extended mode flag in bit 0 of the temperature
+        let temperature = i16::from_be_bytes(data) & !1;
+        let (t_low, t_high) = self.thresholds_raw;
+        Ok((t_low as i16..=t_high as i16).contains(&temperature))
+    }
+
+    /// Read a raw temperature sample into a ring buffer.
+    ///
+    /// The sample is stored in units of 0.0625°C at position `idx` of `ring`,
+    /// after which `idx` is advanced, wrapping around at the end of `ring`.
+    /// This allows filling a buffer without allocation that can later be
+    /// drained in bulk.
+    ///
+    /// If `ring` is empty, `Error::InvalidInputData` is returned without
+    /// accessing the device.
+    pub async fn read_into_ring(
+        &mut self,
+        ring: &mut [i16],
+        idx: &mut usize,
+    ) -> Result<(), Error<E>> {
+        if ring.is_empty() {
+            return Err(Error::InvalidInputData);
+        }
+        let sample = self.read_temperature_counts().await?;
+        let position = *idx % ring.len();
+        ring[position] = sample;
+        *idx = (position + 1) % ring.len();
+        Ok(())
+    }
+
+    /// Read the raw contents of the temperature register.
+    ///
+    /// This returns the value as transmitted by the device, most significant
+    /// byte first, without any shifting. The two's complement temperature is
+    /// left-justified: the 12-bit (normal mode) or 13-bit (extended mode)
+    /// value occupies the upper bits.
+    pub async fn read_temperature_register_raw(&mut self) -> Result<u16, Error<E>> {
+        let data = self.read_register(Register::Temperature).await?;
+        self.sample_count = self.sample_count.wrapping_add(1);
+        Ok(u16::from_be_bytes(data))
+    }
+
+    /// Read the temperature in units of 0.0625°C.
+    pub(crate) async fn read_temperature_counts(&mut self) -> Result<i16, Error<E>> {
+        let data = self.read_register(Register::Temperature).await?;
+        self.sample_count = self.sample_count.wrapping_add(1);
+        Ok(convert_temp_from_register_raw(data[0], data[1]))
+    }
+
+    /// Discard a possibly stale temperature sample.
+    ///
+    /// After changing into continuous conversion mode, the first read may
+    /// return a sample taken before the device was shut down. This waits one
+    /// conversion period and performs a throwaway read so that the next read
+    /// returns a fresh sample.
+    pub async fn flush<D: DelayUs>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        let period_ms = self.config.conversion_rate().period_ms();
+        delay.delay_ms(period_ms as u32).await;
+        self.read_register(Register::Temperature).await?;
+        Ok(())
+    }
+
+    /// Read the mean temperature of `n` consecutive conversions.
+    ///
+    /// The samples are read one conversion period apart according to the
+    /// conversion rate set through this driver. The calibration offset is
+    /// added and the result converted to the unit of this driver instance as
+    /// in [`read_temperature()`](#method.read_temperature).
+    ///
+    /// If `n` is zero, `Error::InvalidInputData` is returned without
+    /// accessing the device.
+    pub async fn read_temperature_averaged<D: DelayUs>(
+        &mut self,
+        n: u8,
+        delay: &mut D,
+    ) -> Result<f32, Error<E>> {
+        if n == 0 {
+            return Err(Error::InvalidInputData);
+        }
+        let period_ms = self.config.conversion_rate().period_ms();
+        let mut sum = 0_i32;
+        for i in 0..n {
+            if i > 0 {
+                delay.delay_ms(period_ms as u32).await;
+            }
+            sum += i32::from(self.read_temperature_counts().await?);
+        }
+        let celsius = sum as f32 / f32::from(n) * 0.0625 + self.calibration_offset;
+        Ok(UNIT::UNIT.convert_celsius(celsius))
+    }
+
+    /// Read the temperature only if a new conversion is available.
+    ///
+    /// Whether a new conversion has occurred since the last read is
+    /// determined from the conversion rate and the current time in
+    /// milliseconds as provided by the caller. The time of the last read is
+    /// kept in `state`. Returns `None` without accessing the device if no new
+    /// conversion is available yet.
+    pub async fn read_temperature_if_new(
+        &mut self,
+        state: &mut SampleState,
+        now_ms: u64,
+    ) -> Result<Option<f32>, Error<E>> {
+        if let Some(last_read_ms) = state.last_read_ms {
+            if self.next_sample_due(last_read_ms, now_ms) > 0 {
+                return Ok(None);
+            }
+        }
+        let temperature = self.read_temperature().await?;
+        state.last_read_ms = Some(now_ms);
+        Ok(Some(temperature))
+    }
+
+    /// Prepare a two-phase temperature read.
+    ///
+    /// This sets the device register pointer to the temperature register so
+    /// that [`complete_read()`](#method.complete_read) only needs to read the
+    /// data. This allows doing the pointer write beforehand and keeping the
+    /// bus access in time-critical contexts like interrupt handlers short.
+    pub async fn initiate_read(&mut self) -> Result<(), Error<E>> {
+        self.set_pointer(Register::Temperature).await
+    }
+
+    /// Complete a two-phase temperature read.
+    ///
+    /// This reads the temperature register data without sending a pointer
+    /// byte. It assumes that [`initiate_read()`](#method.initiate_read) was
+    /// called before and that the pointer was not changed since then. The
+    /// result is the last completed conversion. The calibration offset is
+    /// added and the result converted to the unit of this driver instance as
+    /// in [`read_temperature()`](#method.read_temperature). The packet error
+    /// code is not checked with the `pec` feature.
+    pub async fn complete_read(&mut self) -> Result<f32, Error<E>> {
+        let mut data = [0; 2];
+        self.read_current_register(&mut data).await?;
+        self.sample_count = self.sample_count.wrapping_add(1);
+        let celsius = convert_temp_from_register(data[0], data[1]) + self.calibration_offset;
+        Ok(UNIT::UNIT.convert_celsius(celsius))
     }
 }
 
-impl<I2C, E> Tmp1x2<I2C, mode::OneShot>
+impl<I2C, E, UNIT> Tmp1x2<I2C, mode::OneShot, UNIT>
 where
     I2C: i2c::I2c<Error = E>,
+    UNIT: Unit,
 {
-    /// Read whether the one-shot measurement result is ready.
-    async fn one_shot_measurement_is_ready(&mut self) -> Result<bool, Error<E>> {
-        let mut data = [0; 2];
-        self.i2c
-            .write_read(self.address, &[Register::CONFIG], &mut data)
-            .await
-            .map_err(Error::I2C)?;
-        Ok((data[1] & BitFlagsLow::ONE_SHOT) != 0)
+    /// Wait until the one-shot measurement result is ready.
+    ///
+    /// This waits for the conversion time selected with `timing` and then
+    /// polls the conversion-ready bit until it is set or the maximum
+    /// conversion time of the device has elapsed. Returns `true` if the
+    /// result is ready and `false` if the device is still converting after
+    /// this time.
+    ///
+    /// The same note on clearing alerts as for
+    /// [`is_conversion_ready()`](#method.is_conversion_ready) applies.
+    pub async fn wait_for_one_shot_measurement<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+        timing: ConversionTiming,
+    ) -> Result<bool, Error<E>> {
+        let mut waited_ms = timing.ms();
+        delay.delay_ms(waited_ms).await;
+        loop {
+            if self.is_conversion_ready().await? {
+                return Ok(true);
+            }
+            if waited_ms >= ConversionTiming::Max.ms() {
+                return Ok(false);
+            }
+            delay.delay_ms(READY_POLL_INTERVAL_MS).await;
+            waited_ms += READY_POLL_INTERVAL_MS;
+        }
     }
 
     /// Perform a one-shot temperature measurement.
@@ -40,23 +471,105 @@ where
     /// consumption when continuous temperature monitoring is not required.
     ///
     /// If no temperature conversion was started yet, calling this method
-    /// will start one and return `nb::Error::WouldBlock`. Subsequent calls
-    /// will continue to return `nb::Error::WouldBlock` until the
-    /// temperature measurement is finished. Then it will return the
-    /// measured temperature.
+    /// will start one and return `Error::NotReady`. Subsequent calls check
+    /// the conversion-ready bit and will continue to return
+    /// `Error::NotReady` until the temperature measurement is finished, so
+    /// that no stale value is returned. Then it will return the measured
+    /// temperature in the unit of this driver instance. See also
+    /// [`wait_for_one_shot_measurement()`](#method.wait_for_one_shot_measurement).
     pub async fn read_temperature(&mut self) -> Result<f32, Error<E>> {
-        let mut data = [0; 2];
-        self.i2c
-            .write_read(self.address, &[Register::TEMPERATURE], &mut data)
-            .await
-            .map_err(Error::I2C)?;
-        let temp = convert_temp_from_register(data[0], data[1]);
+        if !self.a_temperature_conversion_was_started {
+            self.trigger_one_shot_measurement().await?;
+            self.a_temperature_conversion_was_started = true;
+            return Err(Error::NotReady);
+        }
+        if !self.is_conversion_ready().await? {
+            return Err(Error::NotReady);
+        }
+        let data = self.read_register(Register::Temperature).await?;
+        let temp = convert_temp_from_register(data[0], data[1]) + self.calibration_offset;
         self.a_temperature_conversion_was_started = false;
-        Ok(temp)
+        self.sample_count = self.sample_count.wrapping_add(1);
+        Ok(UNIT::UNIT.convert_celsius(temp))
+    }
+
+    /// Perform a one-shot temperature measurement with a timeout.
+    ///
+    /// This triggers a measurement if none was started yet and polls for the
+    /// result as [`read_temperature()`](#method.read_temperature) does.
+    /// After triggering, it waits for the conversion time selected with
+    /// `timing` before the first poll and a few milliseconds between later
+    /// polls. The measurement is raced against the `timeout` future, which
+    /// is polled first each time, and `Error::Timeout` is returned as soon as
+    /// it completes, even while a bus transfer or a delay is pending. This
+    /// allows using a timer of the executor as timeout.
+    pub async fn read_temperature_one_shot_with_timeout<D, T>(
+        &mut self,
+        delay: &mut D,
+        timing: ConversionTiming,
+        timeout: T,
+    ) -> Result<f32, Error<E>>
+    where
+        D: DelayUs,
+        T: Future,
+    {
+        let mut timeout = pin!(timeout);
+        let mut measurement = pin!(self.read_temperature_one_shot_polling(delay, timing));
+        poll_fn(|cx| {
+            if timeout.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Err(Error::Timeout));
+            }
+            measurement.as_mut().poll(cx)
+        })
+        .await
+    }
+
+    /// Poll for a one-shot measurement until it is available.
+    async fn read_temperature_one_shot_polling<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+        timing: ConversionTiming,
+    ) -> Result<f32, Error<E>> {
+        loop {
+            let was_started = self.a_temperature_conversion_was_started;
+            match self.read_temperature().await {
+                Err(Error::NotReady) => (),
+                result => return result,
+            }
+            let wait_ms = if was_started {
+                READY_POLL_INTERVAL_MS
+            } else {
+                timing.ms()
+            };
+            delay.delay_ms(wait_ms).await;
+        }
     }
 }
 
-impl<I2C, E, MODE> Tmp1x2<I2C, MODE>
+/// Read the temperature from several devices sharing the same I²C bus.
+///
+/// The temperature register of each of the given addresses is read in
+/// sequence and the results are returned in the same order. This avoids
+/// creating a driver instance for each device for a simple poll.
+///
+/// *Note:* This assumes all devices are in continuous conversion mode and in
+/// normal (not extended) measurement mode, as after power-up.
+pub async fn read_all<I2C, E, const N: usize>(
+    i2c: &mut I2C,
+    addrs: [u8; N],
+) -> Result<[f32; N], Error<E>>
+where
+    I2C: i2c::I2c<Error = E>,
+{
+    let mut temperatures = [0.0; N];
+    for (address, temperature) in addrs.iter().zip(temperatures.iter_mut()) {
+        let data = read_register(i2c, *address, Register::Temperature).await?;
+        *temperature = convert_temp_from_register(data[0], data[1]);
+    }
+    Ok(temperatures)
+}
+
+impl<I2C, E, MODE, UNIT> Tmp1x2<I2C, MODE, UNIT>
 where
     I2C: i2c::I2c<Error = E>,
 {
@@ -71,13 +584,248 @@ where
     /// [AlertPolarity](enum.AlertPolarity.html).
     #[allow(clippy::wrong_self_convention)]
     pub async fn is_comparator_mode_alert_active(&mut self) -> Result<bool, Error<E>> {
-        let mut data = [0; 2];
-        self.i2c
-            .write_read(self.address, &[Register::CONFIG], &mut data)
-            .await
-            .map_err(Error::I2C)?;
-        let is_alert_polarity_high = (data[1] & BitFlagsLow::ALERT_POLARITY) != 0;
-        let alert_status = (data[0] & BitFlagsHigh::ALERT) != 0;
-        Ok(is_alert_polarity_high == alert_status)
+        let data = self.read_register(Register::Config).await?;
+        Ok(is_alert_active(data))
+    }
+
+    /// Read whether a conversion result is ready.
+    ///
+    /// This reads the one-shot/conversion-ready bit of the configuration
+    /// register. In one-shot mode, it reports whether the triggered
+    /// measurement is finished. In continuous conversion mode, the same bit
+    /// reports the conversion-ready status of the device.
+    ///
+    /// *NOTE*: In interrupt thermostat mode, reading any register of the
+    /// device clears a pending alert on the ALERT pin, including this one.
+    /// The device offers no way to poll readiness without a register read.
+    /// This only reads the configuration register and never writes it.
+    pub async fn is_conversion_ready(&mut self) -> Result<bool, Error<E>> {
+        let data = self.read_register(Register::Config).await?;
+        Ok((data[1] & BitFlagsLow::ONE_SHOT) != 0)
+    }
+
+    /// Wait for the ALERT pin to become active.
+    ///
+    /// This waits for the edge of the given pin corresponding to an alert
+    /// becoming active with the given polarity: a rising edge for
+    /// `AlertPolarity::ActiveHigh` and a falling edge for
+    /// `AlertPolarity::ActiveLow`. This does not access the device.
+    ///
+    /// *NOTE*: An alert that is already active when calling this is not
+    /// reported until the pin toggles again. In comparator mode, check
+    /// [`is_comparator_mode_alert_active()`](#method.is_comparator_mode_alert_active)
+    /// first if needed.
+    pub async fn wait_for_alert<P: Wait>(
+        &mut self,
+        pin: &mut P,
+        polarity: AlertPolarity,
+    ) -> Result<(), P::Error> {
+        match polarity {
+            AlertPolarity::ActiveHigh => pin.wait_for_rising_edge().await,
+            AlertPolarity::ActiveLow => pin.wait_for_falling_edge().await,
+        }
+    }
+
+    /// Wait for the ALERT pin to become active for an unmasked threshold.
+    ///
+    /// This waits like [`wait_for_alert()`](#method.wait_for_alert). If
+    /// alerts of one of the thresholds are disabled with
+    /// [`set_alert_on_high()`](#method.set_alert_on_high) or
+    /// [`set_alert_on_low()`](#method.set_alert_on_low), the temperature is
+    /// read after each edge and alerts caused by a disabled threshold are
+    /// ignored. A temperature at or above the cached high threshold is
+    /// attributed to the high threshold, any other to the low threshold.
+    ///
+    /// If both thresholds are enabled, the device is not accessed. If both
+    /// are disabled, this never returns unless an error occurs.
+    pub async fn wait_for_unmasked_alert<P: Wait>(
+        &mut self,
+        pin: &mut P,
+        polarity: AlertPolarity,
+    ) -> Result<(), AlertWaitError<E, P::Error>> {
+        loop {
+            self.wait_for_alert(pin, polarity)
+                .await
+                .map_err(AlertWaitError::Pin)?;
+            if self.alert_enabled == (true, true) {
+                return Ok(());
+            }
+            let data = self
+                .read_register(Register::Temperature)
+                .await
+                .map_err(AlertWaitError::Device)?;
+            if is_alert_unmasked(data, self.thresholds_raw.1, self.alert_enabled) {
+                return Ok(());
+            }
+        }
     }
+
+    /// Verify that the bits that always read as a fixed value match.
+    ///
+    /// The devices have no identification register but the converter
+    /// resolution bits of the configuration register always read as set.
+    /// This reads the configuration register and returns
+    /// `Error::UnexpectedDevice` if they do not. This is a lightweight
+    /// check that the device is present and responding as expected.
+    /// See also [`probe()`](#method.probe).
+    pub async fn verify_fixed_bits(&mut self) -> Result<(), Error<E>> {
+        let data = self.read_register(Register::Config).await?;
+        if fixed_bits_match(data) {
+            Ok(())
+        } else {
+            Err(Error::UnexpectedDevice)
+        }
+    }
+
+    /// Check whether the threshold window programmed on the device is valid.
+    ///
+    /// This reads the low and high temperature threshold registers and
+    /// returns whether the low threshold is less than or equal to the high
+    /// threshold. This is a diagnostic for misconfigurations, for example
+    /// after the device was configured externally.
+    pub async fn check_window(&mut self) -> Result<bool, Error<E>> {
+        let t_low = self.read_register(Register::TLow).await?;
+        let t_high = self.read_register(Register::THigh).await?;
+        // both registers use the same left-justified two's complement format
+        Ok(i16::from_be_bytes(t_low) <= i16::from_be_bytes(t_high))
+    }
+
+    /// Read the configuration register in raw and decoded form.
+    ///
+    /// The raw word has the byte sent first by the device in the upper half,
+    /// as in [`Config::bits()`](struct.Config.html#method.bits), so that it
+    /// can be compared against the bit tables of the datasheet. The cached
+    /// configuration of this driver is not modified.
+    pub async fn read_config_verbose(&mut self) -> Result<(u16, Config), Error<E>> {
+        let [msb, lsb] = self.read_register(Register::Config).await?;
+        Ok((u16::from_be_bytes([msb, lsb]), Config::from_bytes(msb, lsb)))
+    }
+
+    /// Read the conversion mode the device is currently in.
+    ///
+    /// This reads the shutdown bit of the configuration register. It can be
+    /// used to select the right mode transition for this driver after the
+    /// device has been reset externally.
+    pub async fn read_mode(&mut self) -> Result<Mode, Error<E>> {
+        let data = self.read_register(Register::Config).await?;
+        if (data[1] & BitFlagsLow::SHUTDOWN) != 0 {
+            Ok(Mode::OneShot)
+        } else {
+            Ok(Mode::Continuous)
+        }
+    }
+
+    /// Read all registers for diagnostics.
+    ///
+    /// This reads the temperature, configuration, low and high temperature
+    /// threshold registers in this order and returns their raw contents
+    /// together with their decoded values. The thresholds are decoded
+    /// according to the extended mode flag read from the configuration
+    /// register. The cached configuration of this driver is not modified.
+    pub async fn dump(&mut self) -> Result<RegisterDump, Error<E>> {
+        let temperature = self.read_register(Register::Temperature).await?;
+        let config = self.read_register(Register::Config).await?;
+        let t_low = self.read_register(Register::TLow).await?;
+        let t_high = self.read_register(Register::THigh).await?;
+        let decoded_config = Config::from_bytes(config[0], config[1]);
+        let extended_mode = decoded_config.is_extended_mode();
+        Ok(RegisterDump {
+            temperature_raw: u16::from_be_bytes(temperature),
+            config_raw: u16::from_be_bytes(config),
+            t_low_raw: u16::from_be_bytes(t_low),
+            t_high_raw: u16::from_be_bytes(t_high),
+            temperature: convert_temp_from_register(temperature[0], temperature[1]),
+            config: decoded_config,
+            t_low: decode_temperature(t_low[0], t_low[1], extended_mode),
+            t_high: decode_temperature(t_high[0], t_high[1], extended_mode),
+        })
+    }
+
+    /// Read the raw contents of a register.
+    ///
+    /// The data is returned in the order it is sent by the device, that is,
+    /// most significant byte first.
+    ///
+    /// The device keeps the register pointer after each access. If the last
+    /// access through this driver was to the same register, the pointer byte
+    /// is not sent again and only the data is read. This is not done with the
+    /// `pec` feature.
+    pub async fn read_register(&mut self, register: Register) -> Result<[u8; 2], Error<E>> {
+        self.write_dirty_config().await?;
+        let pointer = self.pointer.take();
+        let data = if needs_pointer(pointer, register) {
+            read_register(&mut self.i2c, self.address, register).await?
+        } else {
+            read_register_data(&mut self.i2c, self.address).await?
+        };
+        self.pointer = Some(register);
+        Ok(data)
+    }
+
+    /// Read the register the device pointer is currently set to.
+    ///
+    /// Only the data is read, without sending a pointer byte first. The
+    /// pointer can be selected with [`set_pointer()`](#method.set_pointer).
+    /// The data is stored most significant byte first. The packet error
+    /// code is not checked with the `pec` feature.
+    pub async fn read_current_register(&mut self, buf: &mut [u8; 2]) -> Result<(), Error<E>> {
+        self.write_dirty_config().await?;
+        *buf = read_register_data(&mut self.i2c, self.address).await?;
+        Ok(())
+    }
+}
+
+/// Check the configuration register bits that always read as a fixed value.
+///
+/// These are the converter resolution bits, which always read as set.
+pub(crate) fn fixed_bits_match(config: [u8; 2]) -> bool {
+    (config[1] & BitFlagsLow::RESOLUTION) == BitFlagsLow::RESOLUTION
+}
+
+// both registers use the same left-justified two's complement format
+fn is_alert_unmasked(temperature: [u8; 2], t_high: u16, enabled: (bool, bool)) -> bool {
+    if i16::from_be_bytes(temperature) >= t_high as i16 {
+        enabled.1
+    } else {
+        enabled.0
+    }
+}
+
+pub(crate) fn is_alert_active(config: [u8; 2]) -> bool {
+    let is_alert_polarity_high = (config[1] & BitFlagsLow::ALERT_POLARITY) != 0;
+    let alert_status = (config[0] & BitFlagsHigh::ALERT) != 0;
+    is_alert_polarity_high == alert_status
+}
+
+/// Read the register the device pointer is currently set to.
+async fn read_register_data<I2C, E>(i2c: &mut I2C, address: u8) -> Result<[u8; 2], Error<E>>
+where
+    I2C: i2c::I2c<Error = E>,
+{
+    let mut data = [0; 2];
+    i2c.read(address, &mut data).await.map_err(Error::I2C)?;
+    trace!("read from {:?}: {:?}", address, data);
+    Ok(data)
+}
+
+/// Read a register after sending the register pointer.
+async fn read_register<I2C, E>(
+    i2c: &mut I2C,
+    address: u8,
+    register: Register,
+) -> Result<[u8; 2], Error<E>>
+where
+    I2C: i2c::I2c<Error = E>,
+{
+    let mut data = [0; REGISTER_READ_LEN];
+    i2c.write_read(address, &[register as u8], &mut data)
+        .await
+        .map_err(Error::I2C)?;
+    trace!(
+        "write_read to {:?}: {:?} -> {:?}",
+        address,
+        register as u8,
+        data
+    );
+    register_data(address, register, data)
 }
@@ -0,0 +1,158 @@
+// recording of the I2C transactions when the `test-util` feature is enabled
+
+use embedded_hal_async::i2c::{self, ErrorType, Operation};
+use heapless::Vec;
+
+// all accesses of this driver fit, including the PEC byte
+const MAX_RECORDED_BYTES: usize = 4;
+
+/// Recorded I²C transaction
+///
+/// At most 4 bytes are recorded per transfer, which covers all accesses of
+/// this driver.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedTransaction {
+    /// Read from the device
+    Read {
+        /// Device address
+        address: u8,
+        /// Bytes read
+        data: Vec<u8, MAX_RECORDED_BYTES>,
+    },
+    /// Write to the device
+    Write {
+        /// Device address
+        address: u8,
+        /// Bytes written
+        data: Vec<u8, MAX_RECORDED_BYTES>,
+    },
+    /// Write followed by a read with a repeated start
+    WriteRead {
+        /// Device address
+        address: u8,
+        /// Bytes written
+        write: Vec<u8, MAX_RECORDED_BYTES>,
+        /// Bytes read
+        read: Vec<u8, MAX_RECORDED_BYTES>,
+    },
+}
+
+impl RecordedTransaction {
+    /// Create a read transaction.
+    pub fn read(address: u8, data: &[u8]) -> Self {
+        RecordedTransaction::Read {
+            address,
+            data: record(data),
+        }
+    }
+
+    /// Create a write transaction.
+    pub fn write(address: u8, data: &[u8]) -> Self {
+        RecordedTransaction::Write {
+            address,
+            data: record(data),
+        }
+    }
+
+    /// Create a write-read transaction.
+    pub fn write_read(address: u8, write: &[u8], read: &[u8]) -> Self {
+        RecordedTransaction::WriteRead {
+            address,
+            write: record(write),
+            read: record(read),
+        }
+    }
+}
+
+fn record(data: &[u8]) -> Vec<u8, MAX_RECORDED_BYTES> {
+    data.iter().take(MAX_RECORDED_BYTES).copied().collect()
+}
+
+/// I²C bus wrapper recording all transactions
+///
+/// This forwards all transactions to the wrapped bus and records them,
+/// including failed ones, so that tests of code using this driver can
+/// assert the exact bus traffic. Up to `N` transactions are recorded,
+/// further ones are only forwarded.
+///
+/// This is available with the `test-util` feature.
+#[derive(Debug)]
+pub struct RecordingWrapper<I2C, const N: usize> {
+    i2c: I2C,
+    transactions: Vec<RecordedTransaction, N>,
+}
+
+impl<I2C, const N: usize> RecordingWrapper<I2C, N> {
+    /// Wrap an I²C bus.
+    pub fn new(i2c: I2C) -> Self {
+        RecordingWrapper {
+            i2c,
+            transactions: Vec::new(),
+        }
+    }
+
+    /// Get the recorded transactions in the order they happened.
+    pub fn transactions(&self) -> &[RecordedTransaction] {
+        &self.transactions
+    }
+
+    /// Forget the recorded transactions.
+    pub fn clear(&mut self) {
+        self.transactions.clear();
+    }
+
+    /// Destroy the wrapper, return the I²C bus instance.
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    fn push(&mut self, transaction: RecordedTransaction) {
+        // transactions beyond the capacity are not recorded
+        let _ = self.transactions.push(transaction);
+    }
+}
+
+impl<I2C: ErrorType, const N: usize> ErrorType for RecordingWrapper<I2C, N> {
+    type Error = I2C::Error;
+}
+
+impl<I2C: i2c::I2c, const N: usize> i2c::I2c for RecordingWrapper<I2C, N> {
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.i2c.read(address, read).await;
+        self.push(RecordedTransaction::read(address, read));
+        result
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.i2c.write(address, write).await;
+        self.push(RecordedTransaction::write(address, write));
+        result
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let result = self.i2c.write_read(address, write, read).await;
+        self.push(RecordedTransaction::write_read(address, write, read));
+        result
+    }
+
+    async fn transaction<'a, 'b>(
+        &mut self,
+        address: u8,
+        operations: &'a mut [Operation<'b>],
+    ) -> Result<(), Self::Error> {
+        let result = self.i2c.transaction(address, operations).await;
+        for operation in operations.iter() {
+            let transaction = match operation {
+                Operation::Read(read) => RecordedTransaction::read(address, read),
+                Operation::Write(write) => RecordedTransaction::write(address, write),
+            };
+            self.push(transaction);
+        }
+        result
+    }
+}
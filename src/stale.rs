@@ -0,0 +1,91 @@
+/// Detection of a possibly stuck sensor from identical consecutive readings.
+///
+/// The temperature usually fluctuates by at least one LSB between
+/// conversions. A long run of identical raw readings may indicate that the
+/// sensor stopped converting. See
+/// [`read_temperature_with_health()`](struct.Tmp1x2.html#method.read_temperature_with_health).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StaleDetector {
+    threshold: u16,
+    last: Option<i16>,
+    repeats: u16,
+}
+
+impl StaleDetector {
+    /// Create a new detector flagging readings as stale after `threshold`
+    /// consecutive readings identical to the previous one.
+    pub const fn new(threshold: u16) -> Self {
+        StaleDetector {
+            threshold,
+            last: None,
+            repeats: 0,
+        }
+    }
+
+    /// Record a raw reading in units of 0.0625°C.
+    ///
+    /// Returns whether the readings are considered stale.
+    pub fn update(&mut self, raw: i16) -> bool {
+        if self.last == Some(raw) {
+            self.repeats = self.repeats.saturating_add(1);
+        } else {
+            self.last = Some(raw);
+            self.repeats = 0;
+        }
+        self.is_stale()
+    }
+
+    /// Get whether the readings are considered stale.
+    pub fn is_stale(&self) -> bool {
+        self.repeats >= self.threshold
+    }
+
+    /// Get the number of consecutive readings identical to the previous one.
+    pub fn repeats(&self) -> u16 {
+        self.repeats
+    }
+
+    /// Forget the recorded readings.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.repeats = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StaleDetector;
+
+    #[test]
+    fn flags_identical_readings() {
+        let mut detector = StaleDetector::new(3);
+        assert!(!detector.update(400));
+        assert!(!detector.update(400));
+        assert!(!detector.update(400));
+        assert!(detector.update(400));
+        assert_eq!(3, detector.repeats());
+        assert!(detector.update(400));
+    }
+
+    #[test]
+    fn varying_readings_are_not_stale() {
+        let mut detector = StaleDetector::new(2);
+        for &raw in &[400, 401, 401, 400, 400, 402] {
+            assert!(!detector.update(raw));
+        }
+        assert!(!detector.update(402));
+        assert!(detector.update(402));
+        assert!(!detector.update(403));
+        assert_eq!(0, detector.repeats());
+    }
+
+    #[test]
+    fn can_reset() {
+        let mut detector = StaleDetector::new(1);
+        detector.update(400);
+        assert!(detector.update(400));
+        detector.reset();
+        assert!(!detector.is_stale());
+        assert!(!detector.update(400));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    marker::{mode, unit::Unit},
+    Error, Tmp1x2,
+};
+use embedded_hal_async::i2c;
+
+/// Reading of each conversion in continuous conversion mode only once.
+///
+/// This checks the conversion-ready bit before reading the temperature, so
+/// that polling faster than the conversion rate does not return the same
+/// conversion several times. See
+/// [`is_conversion_ready()`](struct.Tmp1x2.html#method.is_conversion_ready).
+///
+/// The conversion-ready bit is a level and not a latch, so after a
+/// temperature has been returned, a new one is only returned once a poll
+/// has seen the bit cleared again.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ConversionSync {
+    conversions: u32,
+    /// A temperature was returned and the bit has not been seen cleared since.
+    stale: bool,
+}
+
+impl ConversionSync {
+    /// Create a new instance with no conversions read.
+    pub const fn new() -> Self {
+        ConversionSync {
+            conversions: 0,
+            stale: false,
+        }
+    }
+
+    /// Read the temperature if a new conversion is available.
+    ///
+    /// This reads the configuration register and, if the conversion-ready
+    /// bit is set, the temperature register. Returns `None` if no new
+    /// conversion is available, which includes the bit still being set
+    /// from the conversion returned last time. The temperature is returned
+    /// with the calibration offset and in the unit of the driver instance as
+    /// by [`read_temperature()`](struct.Tmp1x2.html#method.read_temperature).
+    ///
+    /// *NOTE*: In interrupt thermostat mode, reading the configuration
+    /// register clears a pending alert.
+    pub async fn read_if_ready<I2C, E, UNIT>(
+        &mut self,
+        dev: &mut Tmp1x2<I2C, mode::Continuous, UNIT>,
+    ) -> Result<Option<f32>, Error<E>>
+    where
+        I2C: i2c::I2c<Error = E>,
+        UNIT: Unit,
+    {
+        if !dev.is_conversion_ready().await? {
+            self.stale = false;
+            return Ok(None);
+        }
+        if self.stale {
+            return Ok(None);
+        }
+        let temperature = dev.read_temperature().await?;
+        self.stale = true;
+        self.conversions = self.conversions.wrapping_add(1);
+        Ok(Some(temperature))
+    }
+
+    /// Get the number of conversions read, wrapping around on overflow.
+    pub fn conversions(&self) -> u32 {
+        self.conversions
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    marker::{mode, unit},
+    Error, Tmp1x2,
+};
+use embedded_hal_async::i2c;
+
+/// Output of the software thermostat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThermostatOutput {
+    /// Heating should be on
+    Heat,
+    /// Cooling should be on
+    Cool,
+    /// Neither heating nor cooling should be on
+    Idle,
+}
+
+/// Software thermostat controller.
+///
+/// This wraps a driver instance in continuous conversion mode and decides
+/// whether heating or cooling should be on from the temperature readings.
+///
+/// Heating is turned on when the temperature falls below
+/// `setpoint - hysteresis` and stays on until the setpoint is reached.
+/// Likewise, cooling is turned on when the temperature rises above
+/// `setpoint + hysteresis` and stays on until the setpoint is reached.
+///
+/// The setpoint and hysteresis are in the unit of the driver instance.
+/// This is independent of the thermostat mode and thresholds of the device.
+#[derive(Debug)]
+pub struct Thermostat<I2C, UNIT = unit::Celsius> {
+    dev: Tmp1x2<I2C, mode::Continuous, UNIT>,
+    setpoint: f32,
+    hysteresis: f32,
+    output: ThermostatOutput,
+}
+
+impl<I2C, UNIT> Thermostat<I2C, UNIT> {
+    /// Create a new thermostat with the given setpoint and hysteresis.
+    ///
+    /// The output is initially `Idle`.
+    pub fn new(dev: Tmp1x2<I2C, mode::Continuous, UNIT>, setpoint: f32, hysteresis: f32) -> Self {
+        Thermostat {
+            dev,
+            setpoint,
+            hysteresis,
+            output: ThermostatOutput::Idle,
+        }
+    }
+
+    /// Destroy the thermostat, return the driver instance.
+    pub fn destroy(self) -> Tmp1x2<I2C, mode::Continuous, UNIT> {
+        self.dev
+    }
+
+    /// Set the setpoint.
+    pub fn set_setpoint(&mut self, setpoint: f32) {
+        self.setpoint = setpoint;
+    }
+
+    /// Set the hysteresis.
+    pub fn set_hysteresis(&mut self, hysteresis: f32) {
+        self.hysteresis = hysteresis;
+    }
+
+    /// Get the output decided on the last update.
+    pub fn output(&self) -> ThermostatOutput {
+        self.output
+    }
+}
+
+impl<I2C, E, UNIT> Thermostat<I2C, UNIT>
+where
+    I2C: i2c::I2c<Error = E>,
+    UNIT: unit::Unit,
+{
+    /// Read the temperature and return whether heating or cooling should
+    /// be on.
+    pub async fn update(&mut self) -> Result<ThermostatOutput, Error<E>> {
+        let temperature = self.dev.read_temperature().await?;
+        self.output = next_output(self.output, temperature, self.setpoint, self.hysteresis);
+        Ok(self.output)
+    }
+}
+
+fn next_output(
+    output: ThermostatOutput,
+    temperature: f32,
+    setpoint: f32,
+    hysteresis: f32,
+) -> ThermostatOutput {
+    match output {
+        ThermostatOutput::Heat if temperature < setpoint => ThermostatOutput::Heat,
+        ThermostatOutput::Cool if temperature > setpoint => ThermostatOutput::Cool,
+        _ if temperature < setpoint - hysteresis => ThermostatOutput::Heat,
+        _ if temperature > setpoint + hysteresis => ThermostatOutput::Cool,
+        _ => ThermostatOutput::Idle,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_output, ThermostatOutput as Out};
+
+    fn run(temperatures: [f32; 6]) -> [Out; 6] {
+        let mut outputs = [Out::Idle; 6];
+        let mut output = Out::Idle;
+        for (temperature, out) in temperatures.iter().zip(outputs.iter_mut()) {
+            output = next_output(output, *temperature, 20.0, 1.0);
+            *out = output;
+        }
+        outputs
+    }
+
+    #[test]
+    fn heats_across_hysteresis_band() {
+        let outputs = run([20.0, 19.5, 18.9, 19.5, 20.0, 19.5]);
+        assert_eq!(
+            [
+                Out::Idle,
+                Out::Idle,
+                Out::Heat,
+                Out::Heat,
+                Out::Idle,
+                Out::Idle
+            ],
+            outputs
+        );
+    }
+
+    #[test]
+    fn cools_across_hysteresis_band() {
+        let outputs = run([20.0, 20.5, 21.1, 20.5, 20.0, 20.5]);
+        assert_eq!(
+            [
+                Out::Idle,
+                Out::Idle,
+                Out::Cool,
+                Out::Cool,
+                Out::Idle,
+                Out::Idle
+            ],
+            outputs
+        );
+    }
+}
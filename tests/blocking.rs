@@ -0,0 +1,325 @@
+#![cfg(all(any(feature = "eh02", feature = "eh1"), not(feature = "pec")))]
+#![feature(async_fn_in_trait)]
+#![allow(incomplete_features)]
+extern crate embedded_hal_mock as hal;
+extern crate tmp1x2;
+use hal::{i2c::Transaction as I2cTransaction, MockError};
+use std::io::ErrorKind;
+use tmp1x2::{
+    blocking::Tmp1x2, marker::mode, AlertPolarity as AP, ConversionRate as CR, DeviceKind, Error,
+    FaultQueue as FQ, Mode, SlaveAddr, ThermostatMode as TM, EXTENDED_MAX_C, EXTENDED_MIN_C,
+};
+
+mod common;
+use common::{
+    BitFlagsHigh as BFH, BitFlagsLow as BFL, I2cMock, Register, DEFAULT_CONFIG_LSB,
+    DEFAULT_CONFIG_MSB, DEVICE_ADDRESS,
+};
+
+fn setup(expectations: &[I2cTransaction]) -> Tmp1x2<I2cMock, mode::Continuous> {
+    Tmp1x2::new(I2cMock::new(expectations), SlaveAddr::default())
+}
+
+macro_rules! assert_near {
+    ($left:expr, $right:expr) => {
+        assert!(($left - $right) < core::f32::EPSILON && ($right - $left) < core::f32::EPSILON);
+    };
+}
+
+#[test]
+fn can_create_and_destroy() {
+    let dev = setup(&[]);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_temperature() {
+    let expectations = [I2cTransaction::write_read(
+        DEVICE_ADDRESS,
+        vec![Register::TEMPERATURE],
+        vec![0b0110_0100, 0],
+    )];
+    let mut dev = setup(&expectations);
+    let value = dev.read_temperature().unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_alert_active() {
+    let expectations = [I2cTransaction::write_read(
+        DEVICE_ADDRESS,
+        vec![Register::CONFIG],
+        vec![
+            DEFAULT_CONFIG_MSB & !BFH::ALERT,
+            DEFAULT_CONFIG_LSB & !BFL::ALERT_POLARITY,
+        ],
+    )];
+    let mut dev = setup(&expectations);
+    assert!(dev.is_comparator_mode_alert_active().unwrap());
+    dev.destroy().done();
+}
+
+fn config_read(msb: u8, lsb: u8) -> I2cTransaction {
+    I2cTransaction::write_read(DEVICE_ADDRESS, vec![Register::CONFIG], vec![msb, lsb])
+}
+
+// the pointer is still set to the configuration register after writing it
+fn repeated_config_read(msb: u8, lsb: u8) -> I2cTransaction {
+    I2cTransaction::read(DEVICE_ADDRESS, vec![msb, lsb])
+}
+
+fn config_write(msb: u8, lsb: u8) -> I2cTransaction {
+    I2cTransaction::write(DEVICE_ADDRESS, vec![Register::CONFIG, msb, lsb])
+}
+
+#[test]
+fn probe_detects_tmp1x2() {
+    let extended_msb = DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE;
+    let expectations = [
+        config_read(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB),
+        config_write(extended_msb, DEFAULT_CONFIG_LSB),
+        repeated_config_read(extended_msb, DEFAULT_CONFIG_LSB),
+        config_write(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB),
+    ];
+    let mut dev = setup(&expectations);
+    assert_eq!(DeviceKind::Tmp1x2, dev.probe().unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+fn probe_detects_unknown_device_from_resolution_bits() {
+    let expectations = [config_read(DEFAULT_CONFIG_MSB, 0)];
+    let mut dev = setup(&expectations);
+    assert_eq!(DeviceKind::Unknown, dev.probe().unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+fn probe_detects_unknown_device_from_extended_mode_bit() {
+    let expectations = [
+        config_read(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB),
+        config_write(DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE, DEFAULT_CONFIG_LSB),
+        repeated_config_read(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB),
+        config_write(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB),
+    ];
+    let mut dev = setup(&expectations);
+    assert_eq!(DeviceKind::Unknown, dev.probe().unwrap());
+    dev.destroy().done();
+}
+
+macro_rules! config_test {
+    ($name:ident, $method:ident $(, $value:expr)*; $expected_lsb:expr, $expected_msb:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = [config_write($expected_msb, $expected_lsb)];
+            let mut dev = setup(&expectations);
+            dev.$method($($value),*).unwrap();
+            dev.destroy().done();
+        }
+    };
+}
+
+config_test!(can_enable_extended_mode, enable_extended_mode;
+    DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE);
+config_test!(can_disable_extended_mode, disable_extended_mode;
+    DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB);
+config_test!(can_set_cr_0_25, set_conversion_rate, CR::_0_25Hz;
+    DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB & !BFH::CONV_RATE1);
+config_test!(can_set_cr_1, set_conversion_rate, CR::_1Hz;
+    DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB & !BFH::CONV_RATE1 | BFH::CONV_RATE0);
+config_test!(can_set_cr_4, set_conversion_rate, CR::_4Hz;
+    DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB);
+config_test!(can_set_cr_8, set_conversion_rate, CR::_8Hz;
+    DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB | BFH::CONV_RATE0);
+config_test!(can_set_fq_1, set_fault_queue, FQ::_1;
+    DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB);
+config_test!(can_set_fq_2, set_fault_queue, FQ::_2;
+    DEFAULT_CONFIG_LSB | BFL::FAULT_QUEUE0, DEFAULT_CONFIG_MSB);
+config_test!(can_set_fq_4, set_fault_queue, FQ::_4;
+    DEFAULT_CONFIG_LSB | BFL::FAULT_QUEUE1, DEFAULT_CONFIG_MSB);
+config_test!(can_set_fq_6, set_fault_queue, FQ::_6;
+    DEFAULT_CONFIG_LSB | BFL::FAULT_QUEUE1 | BFL::FAULT_QUEUE0, DEFAULT_CONFIG_MSB);
+config_test!(can_set_alert_polarity_high, set_alert_polarity, AP::ActiveHigh;
+    DEFAULT_CONFIG_LSB | BFL::ALERT_POLARITY, DEFAULT_CONFIG_MSB);
+config_test!(can_set_alert_polarity_low, set_alert_polarity, AP::ActiveLow;
+    DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB);
+config_test!(can_set_thermostat_mode_interrupt, set_thermostat_mode, TM::Interrupt;
+    DEFAULT_CONFIG_LSB | BFL::THERMOSTAT, DEFAULT_CONFIG_MSB);
+config_test!(can_set_thermostat_mode_comparator, set_thermostat_mode, TM::Comparator;
+    DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB);
+
+macro_rules! threshold_test {
+    ($name:ident, $method:ident, $register:ident, $value:expr, $expected_msb:expr, $expected_lsb:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = [I2cTransaction::write(
+                DEVICE_ADDRESS,
+                vec![Register::$register, $expected_msb, $expected_lsb],
+            )];
+            let mut dev = setup(&expectations);
+            dev.$method($value).unwrap();
+            dev.destroy().done();
+        }
+    };
+}
+
+threshold_test!(
+    can_set_high_threshold,
+    set_high_temperature_threshold,
+    T_HIGH,
+    127.9375,
+    0b0111_1111,
+    0b1111_0000
+);
+threshold_test!(
+    can_set_low_threshold,
+    set_low_temperature_threshold,
+    T_LOW,
+    -25.0,
+    0b1110_0111,
+    0
+);
+threshold_test!(
+    high_threshold_is_capped,
+    set_high_temperature_threshold,
+    T_HIGH,
+    200.0,
+    0b0111_1111,
+    0b1111_0000
+);
+
+#[test]
+fn can_set_threshold_in_extended_mode() {
+    let expectations = [
+        config_write(DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE, DEFAULT_CONFIG_LSB),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::T_HIGH, 0b0111_1111, 0b1111_0000],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    dev.enable_extended_mode().unwrap();
+    assert_eq!((EXTENDED_MIN_C, EXTENDED_MAX_C), dev.temperature_range());
+    dev.set_high_temperature_threshold(255.875).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn boosted_rate_is_restored() {
+    let expectations = [
+        config_write(DEFAULT_CONFIG_MSB | BFH::CONV_RATE0, DEFAULT_CONFIG_LSB),
+        I2cTransaction::write_read(
+            DEVICE_ADDRESS,
+            vec![Register::TEMPERATURE],
+            vec![0b0110_0100, 0],
+        ),
+        config_write(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB),
+    ];
+    let mut dev = setup(&expectations);
+    let value = dev
+        .with_boosted_rate(CR::_8Hz, |dev| dev.read_temperature())
+        .unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn boosted_rate_is_restored_on_error() {
+    let expectations = [
+        config_write(DEFAULT_CONFIG_MSB | BFH::CONV_RATE0, DEFAULT_CONFIG_LSB),
+        I2cTransaction::write_read(
+            DEVICE_ADDRESS,
+            vec![Register::TEMPERATURE],
+            vec![0b0110_0100, 0],
+        )
+        .with_error(MockError::Io(ErrorKind::Other)),
+        config_write(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB),
+    ];
+    let mut dev = setup(&expectations);
+    let result = dev.with_boosted_rate(CR::_8Hz, |dev| dev.read_temperature());
+    assert!(matches!(result, Err(Error::I2C(_))));
+    dev.destroy().done();
+}
+
+#[test]
+fn can_change_into_one_shot_and_back() {
+    let expectations = [
+        config_write(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB | BFL::SHUTDOWN),
+        config_write(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB),
+    ];
+    let dev = setup(&expectations);
+    let dev = dev.into_one_shot().unwrap();
+    let dev = dev.into_continuous().unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_mode() {
+    let expectations = [config_read(
+        DEFAULT_CONFIG_MSB,
+        DEFAULT_CONFIG_LSB | BFL::SHUTDOWN,
+    )];
+    let mut dev = setup(&expectations);
+    assert_eq!(Mode::OneShot, dev.read_mode().unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+fn repeated_read_does_not_send_pointer() {
+    let expectations = [
+        I2cTransaction::write_read(
+            DEVICE_ADDRESS,
+            vec![Register::TEMPERATURE],
+            vec![0b0110_0100, 0],
+        ),
+        I2cTransaction::read(DEVICE_ADDRESS, vec![0b0001_1001, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let first = dev.read_temperature().unwrap();
+    let second = dev.read_temperature().unwrap();
+    assert_near!(100.0, first);
+    assert_near!(25.0, second);
+    dev.destroy().done();
+}
+
+#[test]
+fn calibration_offset_is_added() {
+    let expectations = [I2cTransaction::write_read(
+        DEVICE_ADDRESS,
+        vec![Register::TEMPERATURE],
+        vec![0b0001_1001, 0],
+    )];
+    let mut dev = setup(&expectations);
+    dev.set_calibration_offset(-1.5);
+    let value = dev.read_temperature().unwrap();
+    assert_near!(23.5, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_temperature_in_one_shot() {
+    let expectations = [
+        config_write(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB | BFL::SHUTDOWN),
+        config_write(
+            DEFAULT_CONFIG_MSB,
+            DEFAULT_CONFIG_LSB | BFL::SHUTDOWN | BFL::ONE_SHOT,
+        ),
+        repeated_config_read(DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB | BFL::SHUTDOWN),
+        repeated_config_read(
+            DEFAULT_CONFIG_MSB,
+            DEFAULT_CONFIG_LSB | BFL::SHUTDOWN | BFL::ONE_SHOT,
+        ),
+        I2cTransaction::write_read(
+            DEVICE_ADDRESS,
+            vec![Register::TEMPERATURE],
+            vec![0b0110_0100, 0],
+        ),
+    ];
+    let mut dev = setup(&expectations).into_one_shot().unwrap();
+    assert!(matches!(dev.read_temperature(), Err(Error::NotReady)));
+    assert!(matches!(dev.read_temperature(), Err(Error::NotReady)));
+    let value = dev.read_temperature().unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
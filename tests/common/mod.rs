@@ -1,6 +1,10 @@
 extern crate embedded_hal_mock as hal;
 extern crate tmp1x2;
-use hal::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use core::future::Future;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+use embedded_hal_async::{delay::DelayUs, digital, i2c};
+use hal::i2c::Transaction as I2cTransaction;
 use tmp1x2::{marker, SlaveAddr, Tmp1x2};
 
 pub const DEVICE_ADDRESS: u8 = 0b100_1000;
@@ -41,6 +45,389 @@ impl BitFlagsHigh {
 pub const DEFAULT_CONFIG_MSB: u8 = BitFlagsHigh::CONV_RATE1 | BitFlagsHigh::ALERT;
 pub const DEFAULT_CONFIG_LSB: u8 = BitFlagsLow::RESOLUTION;
 
+/// Error returned by the I²C mock.
+#[derive(Debug)]
+pub struct MockError(pub hal::MockError);
+
+impl i2c::Error for MockError {
+    fn kind(&self) -> i2c::ErrorKind {
+        i2c::ErrorKind::Other
+    }
+}
+
+/// Async I²C mock forwarding to the blocking `embedded-hal-mock` I²C mock.
+#[derive(Debug)]
+pub struct I2cMock(hal::i2c::Mock);
+
+#[allow(unused)]
+impl I2cMock {
+    pub fn new(expectations: &[I2cTransaction]) -> Self {
+        I2cMock(hal::i2c::Mock::new(expectations))
+    }
+
+    pub fn done(&mut self) {
+        self.0.done();
+    }
+}
+
+impl i2c::ErrorType for I2cMock {
+    type Error = MockError;
+}
+
+impl i2c::I2c for I2cMock {
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        Read::read(&mut self.0, address, read).map_err(MockError)
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        Write::write(&mut self.0, address, write).map_err(MockError)
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        WriteRead::write_read(&mut self.0, address, write, read).map_err(MockError)
+    }
+
+    async fn transaction<'a, 'b>(
+        &mut self,
+        address: u8,
+        operations: &'a mut [i2c::Operation<'b>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                i2c::Operation::Read(read) => i2c::I2c::read(self, address, read).await?,
+                i2c::Operation::Write(write) => i2c::I2c::write(self, address, write).await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl Read for I2cMock {
+    type Error = MockError;
+
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        Read::read(&mut self.0, address, read).map_err(MockError)
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl Write for I2cMock {
+    type Error = MockError;
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        Write::write(&mut self.0, address, write).map_err(MockError)
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl WriteRead for I2cMock {
+    type Error = MockError;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        WriteRead::write_read(&mut self.0, address, write, read).map_err(MockError)
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl embedded_hal_1::i2c::I2c for I2cMock {
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        Read::read(&mut self.0, address, read).map_err(MockError)
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        Write::write(&mut self.0, address, write).map_err(MockError)
+    }
+
+    fn write_iter<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let bytes: Vec<u8> = bytes.into_iter().collect();
+        Write::write(&mut self.0, address, &bytes).map_err(MockError)
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        WriteRead::write_read(&mut self.0, address, write, read).map_err(MockError)
+    }
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: u8,
+        bytes: B,
+        read: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let bytes: Vec<u8> = bytes.into_iter().collect();
+        WriteRead::write_read(&mut self.0, address, &bytes, read).map_err(MockError)
+    }
+
+    fn transaction<'a>(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c::Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                i2c::Operation::Read(read) => Read::read(&mut self.0, address, read),
+                i2c::Operation::Write(write) => Write::write(&mut self.0, address, write),
+            }
+            .map_err(MockError)?;
+        }
+        Ok(())
+    }
+
+    fn transaction_iter<'a, O>(&mut self, address: u8, operations: O) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = i2c::Operation<'a>>,
+    {
+        for mut operation in operations {
+            embedded_hal_1::i2c::I2c::transaction(
+                self,
+                address,
+                core::slice::from_mut(&mut operation),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Delay mock recording the requested delays in microseconds.
+#[derive(Debug, Default)]
+pub struct DelayMock {
+    pub delays_us: Vec<u32>,
+}
+
+impl DelayUs for DelayMock {
+    async fn delay_us(&mut self, us: u32) {
+        self.delays_us.push(us);
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.delays_us.push(ms * 1000);
+    }
+}
+
+/// Pin edge or level waited for by `PinMock`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PinWait {
+    High,
+    Low,
+    RisingEdge,
+    FallingEdge,
+    AnyEdge,
+}
+
+/// Wait pin mock recording the waits requested.
+#[derive(Debug, Default)]
+pub struct PinMock {
+    pub waits: Vec<PinWait>,
+}
+
+impl digital::ErrorType for PinMock {
+    type Error = core::convert::Infallible;
+}
+
+impl digital::Wait for PinMock {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.waits.push(PinWait::High);
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.waits.push(PinWait::Low);
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.waits.push(PinWait::RisingEdge);
+        Ok(())
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.waits.push(PinWait::FallingEdge);
+        Ok(())
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.waits.push(PinWait::AnyEdge);
+        Ok(())
+    }
+}
+
+fn noop_waker() -> Waker {
+    fn noop_raw_waker() -> RawWaker {
+        fn no_op(_: *const ()) {}
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, no_op, no_op, no_op);
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    unsafe { Waker::from_raw(noop_raw_waker()) }
+}
+
+/// Run a future to completion on the current thread.
+///
+/// The mock never returns `Poll::Pending` so a no-op waker is enough.
+#[allow(unused)]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = noop_waker();
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// Poll a future once and drop it.
+///
+/// Returns whether the future completed.
+#[allow(unused)]
+pub fn poll_once_and_drop<F: Future>(future: F) -> bool {
+    let waker = noop_waker();
+    let mut context = Context::from_waker(&waker);
+    Box::pin(future).as_mut().poll(&mut context).is_ready()
+}
+
+/// Future returning `Poll::Pending` once.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: core::pin::Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+/// Return a future that completes on its second poll.
+#[allow(unused)]
+pub fn yield_now() -> impl Future<Output = ()> {
+    YieldNow(false)
+}
+
+/// I²C mock whose transfers stay pending forever once the given number of
+/// transfers have been done.
+#[allow(unused)]
+#[derive(Debug)]
+pub struct StallingI2cMock(pub I2cMock, pub usize);
+
+impl StallingI2cMock {
+    async fn next_transfer(&mut self) {
+        if self.1 == 0 {
+            core::future::pending::<()>().await;
+        }
+        self.1 -= 1;
+    }
+}
+
+impl i2c::ErrorType for StallingI2cMock {
+    type Error = MockError;
+}
+
+impl i2c::I2c for StallingI2cMock {
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.next_transfer().await;
+        i2c::I2c::read(&mut self.0, address, read).await
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.next_transfer().await;
+        i2c::I2c::write(&mut self.0, address, write).await
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.next_transfer().await;
+        i2c::I2c::write_read(&mut self.0, address, write, read).await
+    }
+
+    async fn transaction<'a, 'b>(
+        &mut self,
+        address: u8,
+        operations: &'a mut [i2c::Operation<'b>],
+    ) -> Result<(), Self::Error> {
+        self.next_transfer().await;
+        i2c::I2c::transaction(&mut self.0, address, operations).await
+    }
+}
+
+/// I²C mock that yields after each transfer.
+///
+/// This allows dropping a driver future after the transfer happened but
+/// before it completed.
+#[derive(Debug)]
+pub struct YieldingI2cMock(pub I2cMock);
+
+impl i2c::ErrorType for YieldingI2cMock {
+    type Error = MockError;
+}
+
+impl i2c::I2c for YieldingI2cMock {
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = i2c::I2c::read(&mut self.0, address, read).await;
+        YieldNow(false).await;
+        result
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        let result = i2c::I2c::write(&mut self.0, address, write).await;
+        YieldNow(false).await;
+        result
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let result = i2c::I2c::write_read(&mut self.0, address, write, read).await;
+        YieldNow(false).await;
+        result
+    }
+
+    async fn transaction<'a, 'b>(
+        &mut self,
+        address: u8,
+        operations: &'a mut [i2c::Operation<'b>],
+    ) -> Result<(), Self::Error> {
+        let result = i2c::I2c::transaction(&mut self.0, address, operations).await;
+        YieldNow(false).await;
+        result
+    }
+}
+
+#[allow(unused)]
 pub fn setup(expectations: &[I2cTransaction]) -> Tmp1x2<I2cMock, marker::mode::Continuous> {
     let i2c = I2cMock::new(expectations);
     Tmp1x2::new(i2c, SlaveAddr::default())
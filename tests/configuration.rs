@@ -1,12 +1,19 @@
+#![feature(async_fn_in_trait)]
+#![allow(incomplete_features)]
 extern crate embedded_hal_mock as hal;
 extern crate tmp1x2;
 use hal::i2c::Transaction as I2cTransaction;
-use tmp1x2::{AlertPolarity as AP, ConversionRate as CR, FaultQueue as FQ, ThermostatMode as TM};
+use tmp1x2::{
+    requires_shutdown, AlertPolarity as AP, Config, ConfigChange, ConfigField,
+    ConversionRate as CR, DesiredConfig, DeviceKind, Error, ExtendedTemp, FaultQueue as FQ,
+    NormalTemp, SlaveAddr, ThermostatMode as TM, Tmp1x2, EXTENDED_MAX_C, EXTENDED_MIN_C,
+    NORMAL_MAX_C, NORMAL_MIN_C,
+};
 
 mod common;
 use common::{
-    setup, BitFlagsHigh as BFH, BitFlagsLow as BFL, Register, DEFAULT_CONFIG_LSB as DEFAULT_LSB,
-    DEFAULT_CONFIG_MSB as DEFAULT_MSB, DEVICE_ADDRESS,
+    block_on, setup, BitFlagsHigh as BFH, BitFlagsLow as BFL, I2cMock, Register,
+    DEFAULT_CONFIG_LSB as DEFAULT_LSB, DEFAULT_CONFIG_MSB as DEFAULT_MSB, DEVICE_ADDRESS,
 };
 
 fn get_write_expectation(register: u8, lsb: u8, msb: u8) -> [I2cTransaction; 1] {
@@ -23,7 +30,7 @@ macro_rules! config_test {
             let expectations =
                 get_write_expectation(Register::CONFIG, $expected_lsb, $expected_msb);
             let mut dev = setup(&expectations);
-            dev.$method().unwrap();
+            block_on(dev.$method()).unwrap();
             dev.destroy().done();
         }
     };
@@ -36,7 +43,7 @@ fn can_change_into_one_shot() {
         vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | 1],
     )];
     let dev = setup(&expectations);
-    let dev = dev.into_one_shot().unwrap();
+    let dev = block_on(dev.into_one_shot()).unwrap();
     dev.destroy().done();
 }
 
@@ -53,8 +60,8 @@ fn can_change_into_continuous() {
         ),
     ];
     let dev = setup(&expectations);
-    let dev = dev.into_one_shot().unwrap();
-    let dev = dev.into_continuous().unwrap();
+    let dev = block_on(dev.into_one_shot()).unwrap();
+    let dev = block_on(dev.into_continuous()).unwrap();
     dev.destroy().done();
 }
 
@@ -78,7 +85,7 @@ macro_rules! config_value_test {
             let expectations =
                 get_write_expectation(Register::CONFIG, $expected_lsb, $expected_msb);
             let mut dev = setup(&expectations);
-            dev.$method($value).unwrap();
+            block_on(dev.$method($value)).unwrap();
             dev.destroy().done();
         }
     };
@@ -178,7 +185,7 @@ macro_rules! set_value_test {
         fn $name() {
             let expectations = get_write_expectation($register, $expected_lsb, $expected_msb);
             let mut dev = setup(&expectations);
-            dev.$method($value).unwrap();
+            block_on(dev.$method($value)).unwrap();
             dev.destroy().done();
         }
     };
@@ -235,7 +242,1196 @@ fn can_set_extended_high_temp_threshold() {
         ),
     ];
     let mut dev = setup(&expectations);
-    dev.enable_extended_mode().unwrap();
-    dev.set_high_temperature_threshold(255.875).unwrap();
+    block_on(dev.enable_extended_mode()).unwrap();
+    block_on(dev.set_high_temperature_threshold(255.875)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn can_set_range_checked_normal_thresholds() {
+    let expectations = [
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH, 0b0001_1110, 0]),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_LOW, 0b1111_0110, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.set_high_temperature_threshold_normal(NormalTemp::new(30.0).unwrap())).unwrap();
+    block_on(dev.set_low_temperature_threshold_normal(NormalTemp::new(-10.0).unwrap())).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn can_set_range_checked_extended_thresholds() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_LSB,
+            ],
+        ),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH, 0b0110_0100, 0]),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_LOW, 0b1110_1100, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.enable_extended_mode()).unwrap();
+    let high = ExtendedTemp::new(200.0).unwrap();
+    block_on(dev.set_high_temperature_threshold_extended(high)).unwrap();
+    let low = ExtendedTemp::new(-40.0).unwrap();
+    block_on(dev.set_low_temperature_threshold_extended(low)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn extended_thresholds_require_extended_mode() {
+    let mut dev = setup(&[]);
+    let temperature = ExtendedTemp::new(200.0).unwrap();
+    assert!(matches!(
+        block_on(dev.set_high_temperature_threshold_extended(temperature)),
+        Err(Error::InvalidInputData)
+    ));
+    assert!(matches!(
+        block_on(dev.set_low_temperature_threshold_extended(temperature)),
+        Err(Error::InvalidInputData)
+    ));
+    dev.destroy().done();
+}
+
+#[test]
+fn can_set_fault_queue_for_noise() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB | BFL::FAULT_QUEUE1,
+        DEFAULT_MSB,
+    );
+    let mut dev = setup(&expectations);
+    block_on(dev.set_fault_queue_for_noise(3)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn can_copy_config_from_other_device() {
+    let config_msb = DEFAULT_MSB | BFH::EXTENDED_MODE;
+    let config_lsb = DEFAULT_LSB | BFL::FAULT_QUEUE1;
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, config_msb, DEFAULT_LSB],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, config_msb, config_lsb],
+        ),
+    ];
+    let mut source = setup(&expectations);
+    block_on(source.enable_extended_mode()).unwrap();
+    block_on(source.set_fault_queue(FQ::_4)).unwrap();
+
+    let expectations = get_write_expectation(Register::CONFIG, config_lsb, config_msb);
+    let mut dev = setup(&expectations);
+    block_on(dev.copy_config_from(&source)).unwrap();
+    assert_eq!(source.save_state().config, dev.save_state().config);
+    source.destroy().done();
+    dev.destroy().done();
+}
+
+#[test]
+fn copy_config_keeps_conversion_mode() {
+    let expectations = [I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+    )];
+    let source = block_on(setup(&expectations).into_one_shot()).unwrap();
+
+    let expectations = get_write_expectation(Register::CONFIG, DEFAULT_LSB, DEFAULT_MSB);
+    let mut dev = setup(&expectations);
+    block_on(dev.copy_config_from(&source)).unwrap();
+    source.destroy().done();
+    dev.destroy().done();
+}
+
+#[test]
+fn copy_config_brackets_safe_config_changes() {
+    let extended_msb = DEFAULT_MSB | BFH::EXTENDED_MODE;
+    let mut source = setup(&get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB,
+        extended_msb,
+    ));
+    block_on(source.enable_extended_mode()).unwrap();
+
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, extended_msb, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, extended_msb, DEFAULT_LSB],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    dev.set_safe_config_changes(true);
+    block_on(dev.copy_config_from(&source)).unwrap();
+    source.destroy().done();
+    dev.destroy().done();
+}
+
+#[test]
+fn copy_config_is_not_deferred() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+    ];
+    let source = setup(&[]);
+    let mut dev = setup(&expectations);
+    dev.set_deferred_config_writes(true);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    block_on(dev.copy_config_from(&source)).unwrap();
+    source.destroy().done();
+    dev.destroy().done();
+}
+
+#[test]
+fn next_sample_is_due_after_default_period() {
+    let dev = setup(&[]);
+    assert_eq!(250, dev.next_sample_due(1000, 1000));
+    assert_eq!(150, dev.next_sample_due(1000, 1100));
+    assert_eq!(0, dev.next_sample_due(1000, 1250));
+    assert_eq!(0, dev.next_sample_due(1000, 5000));
+    dev.destroy().done();
+}
+
+#[test]
+fn next_sample_due_follows_conversion_rate() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB,
+        DEFAULT_MSB & !BFH::CONV_RATE1 & !BFH::CONV_RATE0,
+    );
+    let mut dev = setup(&expectations);
+    block_on(dev.set_conversion_rate(CR::_0_25Hz)).unwrap();
+    assert_eq!(3000, dev.next_sample_due(0, 1000));
+    assert_eq!(0, dev.next_sample_due(0, 4000));
+    dev.destroy().done();
+}
+
+#[test]
+fn next_sample_due_handles_clock_going_backwards() {
+    let dev = setup(&[]);
+    assert_eq!(250, dev.next_sample_due(1000, 500));
+    dev.destroy().done();
+}
+
+#[test]
+fn can_estimate_bus_bytes_per_second_at_default_rate() {
+    let dev = setup(&[]);
+    assert_eq!(8, dev.bus_bytes_per_second());
+    dev.destroy().done();
+}
+
+macro_rules! bus_bytes_test {
+    ($name:ident, $rate:expr, $msb:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = get_write_expectation(Register::CONFIG, DEFAULT_LSB, $msb);
+            let mut dev = setup(&expectations);
+            block_on(dev.set_conversion_rate($rate)).unwrap();
+            assert_eq!($expected, dev.bus_bytes_per_second());
+            dev.destroy().done();
+        }
+    };
+}
+
+bus_bytes_test!(
+    bus_bytes_per_second_at_0_25hz,
+    CR::_0_25Hz,
+    DEFAULT_MSB & !BFH::CONV_RATE1 & !BFH::CONV_RATE0,
+    1
+);
+bus_bytes_test!(
+    bus_bytes_per_second_at_8hz,
+    CR::_8Hz,
+    DEFAULT_MSB | BFH::CONV_RATE0,
+    16
+);
+
+macro_rules! saturating_test {
+    ($name:ident, $method:ident, $value:expr, $register:expr, $lsb:expr, $msb:expr, $clamped:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = get_write_expectation($register, $lsb, $msb);
+            let mut dev = setup(&expectations);
+            let was_clamped = block_on(dev.$method($value)).unwrap();
+            assert_eq!($clamped, was_clamped);
+            dev.destroy().done();
+        }
+    };
+}
+
+saturating_test!(
+    high_temp_th_in_range_is_not_clamped,
+    set_high_temperature_threshold_saturating,
+    127.9375,
+    Register::T_HIGH,
+    0b1111_0000,
+    0b0111_1111,
+    false
+);
+saturating_test!(
+    high_temp_th_out_of_range_is_clamped,
+    set_high_temperature_threshold_saturating,
+    150.0,
+    Register::T_HIGH,
+    0b1111_0000,
+    0b0111_1111,
+    true
+);
+saturating_test!(
+    low_temp_th_in_range_is_not_clamped,
+    set_low_temperature_threshold_saturating,
+    -128.0,
+    Register::T_LOW,
+    0,
+    0b1000_0000,
+    false
+);
+saturating_test!(
+    low_temp_th_out_of_range_is_clamped,
+    set_low_temperature_threshold_saturating,
+    -130.0,
+    Register::T_LOW,
+    0,
+    0b1000_0000,
+    true
+);
+
+#[test]
+fn extended_high_temp_th_in_range_is_not_clamped() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_LSB,
+            ],
+        ),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH, 0b0100_1011, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.enable_extended_mode()).unwrap();
+    let was_clamped = block_on(dev.set_high_temperature_threshold_saturating(150.0)).unwrap();
+    assert!(!was_clamped);
+    dev.destroy().done();
+}
+
+macro_rules! actual_threshold_test {
+    ($name:ident, $method:ident, $value:expr, $register:expr, $lsb:expr, $msb:expr, $actual:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = get_write_expectation($register, $lsb, $msb);
+            let mut dev = setup(&expectations);
+            let actual = block_on(dev.$method($value)).unwrap();
+            assert_eq!($actual, actual);
+            dev.destroy().done();
+        }
+    };
+}
+
+actual_threshold_test!(
+    high_temp_th_actual_is_exact,
+    set_high_temperature_threshold_actual,
+    80.0625,
+    Register::T_HIGH,
+    0b0001_0000,
+    0b0101_0000,
+    80.0625
+);
+actual_threshold_test!(
+    high_temp_th_actual_is_quantized,
+    set_high_temperature_threshold_actual,
+    80.1,
+    Register::T_HIGH,
+    0b0010_0000,
+    0b0101_0000,
+    80.125
+);
+actual_threshold_test!(
+    high_temp_th_actual_is_clamped,
+    set_high_temperature_threshold_actual,
+    150.0,
+    Register::T_HIGH,
+    0b1111_0000,
+    0b0111_1111,
+    127.9375
+);
+actual_threshold_test!(
+    low_temp_th_actual_is_quantized,
+    set_low_temperature_threshold_actual,
+    -10.1,
+    Register::T_LOW,
+    0b1110_0000,
+    0b1111_0101,
+    -10.125
+);
+
+#[test]
+fn can_set_thermostat_setpoint_fahrenheit() {
+    let expectations = [
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_LOW, 0x15, 0x20]),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH, 0x17, 0x50]),
+    ];
+    let mut dev = setup(&expectations);
+    // 70°F = 21.11°C and 74°F = 23.33°C
+    block_on(dev.set_thermostat_setpoint_fahrenheit(72.0, 2.0)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn thermostat_setpoint_fahrenheit_out_of_range_is_rejected() {
+    let mut dev = setup(&[]);
+    // 262°F = 127.78°C but 266°F = 130°C
+    let result = block_on(dev.set_thermostat_setpoint_fahrenheit(264.0, 2.0));
+    assert!(matches!(result, Err(Error::InvalidInputData)));
+    dev.destroy().done();
+}
+
+#[test]
+fn thermostat_setpoint_fahrenheit_with_negative_hysteresis_is_rejected() {
+    let mut dev = setup(&[]);
+    let result = block_on(dev.set_thermostat_setpoint_fahrenheit(72.0, -2.0));
+    assert!(matches!(result, Err(Error::InvalidInputData)));
+    dev.destroy().done();
+}
+
+#[test]
+fn register_write_is_a_single_transaction() {
+    // All three bytes must be sent in one write so that high-speed mode works.
+    let expectations = [I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::T_LOW, 0b0001_1001, 0],
+    )];
+    let mut dev = setup(&expectations);
+    block_on(dev.set_low_temperature_threshold(25.0)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn normal_mode_temperature_resolution() {
+    let dev = setup(&[]);
+    assert_eq!(12, dev.temperature_resolution_bits());
+    assert_eq!(0.0625, dev.temperature_lsb_celsius());
+    dev.destroy().done();
+}
+
+#[test]
+fn extended_mode_temperature_resolution() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB,
+        DEFAULT_MSB | BFH::EXTENDED_MODE,
+    );
+    let mut dev = setup(&expectations);
+    block_on(dev.enable_extended_mode()).unwrap();
+    assert_eq!(13, dev.temperature_resolution_bits());
+    assert_eq!(0.0625, dev.temperature_lsb_celsius());
+    dev.destroy().done();
+}
+
+#[test]
+fn into_parts_returns_last_written_config() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_LSB,
+            ],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::T_HIGH, 0b0111_1111, 0b1111_0000],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.enable_extended_mode()).unwrap();
+    let (i2c, config) = dev.into_parts();
+    assert_ne!(Config::default(), config);
+    // the recreated driver still encodes thresholds for extended mode
+    let mut dev = Tmp1x2::new_with_config(i2c, SlaveAddr::default(), config);
+    block_on(dev.set_high_temperature_threshold(255.875)).unwrap();
+    let (mut i2c, new_config) = dev.into_parts();
+    assert_eq!(config, new_config);
+    i2c.done();
+}
+
+#[test]
+fn new_with_config_clears_shutdown() {
+    let expectations = [I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+    )];
+    let dev = setup(&expectations);
+    let dev = block_on(dev.into_one_shot()).unwrap();
+    let (i2c, config) = dev.into_parts();
+    assert!(config.contains(Config::SHUTDOWN));
+    let dev = Tmp1x2::new_with_config(i2c, SlaveAddr::default(), config);
+    let (mut i2c, config) = dev.into_parts();
+    assert_eq!(Config::default(), config);
+    i2c.done();
+}
+
+#[test]
+fn reconfigure_with_same_config_does_nothing() {
+    let mut dev = setup(&[]);
+    block_on(dev.reconfigure(Config::default())).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn reconfigure_with_different_config_writes_once() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB,
+        DEFAULT_MSB | BFH::EXTENDED_MODE,
+    );
+    let mut dev = setup(&expectations);
+    block_on(dev.enable_extended_mode()).unwrap();
+    let (mut i2c, target) = dev.into_parts();
+    i2c.done();
+    let mut dev = setup(&expectations);
+    block_on(dev.reconfigure(target)).unwrap();
+    block_on(dev.reconfigure(target)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn reconfigure_ignores_read_only_bits() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB,
+        DEFAULT_MSB | BFH::EXTENDED_MODE,
+    );
+    let mut dev = setup(&expectations);
+    // as read back while an alert is active
+    let target = Config::from_bytes(DEFAULT_MSB & !BFH::ALERT, DEFAULT_LSB & !BFL::RESOLUTION);
+    block_on(dev.reconfigure(target)).unwrap();
+    let target = Config::from_bytes((DEFAULT_MSB & !BFH::ALERT) | BFH::EXTENDED_MODE, 0);
+    block_on(dev.reconfigure(target)).unwrap();
+    block_on(dev.reconfigure(target)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn reconfigure_keeps_conversion_mode() {
+    let mut dev = setup(&get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB,
+        DEFAULT_MSB | BFH::EXTENDED_MODE,
+    ));
+    block_on(dev.enable_extended_mode()).unwrap();
+    let (mut i2c, target) = dev.into_parts();
+    i2c.done();
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_LSB | BFL::SHUTDOWN,
+            ],
+        ),
+    ];
+    let dev = setup(&expectations);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    block_on(dev.reconfigure(target)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn scoped_config_restores_previous_config() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB | BFH::CONV_RATE0, DEFAULT_LSB],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    let mut guard = dev.scoped_config();
+    block_on(guard.set_conversion_rate(CR::_8Hz)).unwrap();
+    block_on(guard.restore()).unwrap();
+    let (mut i2c, config) = dev.into_parts();
+    assert_eq!(Config::default(), config);
+    i2c.done();
+}
+
+#[test]
+fn dropped_scoped_config_is_restored_by_next_operation() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB | BFH::CONV_RATE0, DEFAULT_LSB],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB],
+        ),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::TEMPERATURE]),
+    ];
+    let mut dev = setup(&expectations);
+    {
+        let mut guard = dev.scoped_config();
+        block_on(guard.set_conversion_rate(CR::_8Hz)).unwrap();
+    }
+    block_on(dev.set_pointer(tmp1x2::Register::Temperature)).unwrap();
+    let (mut i2c, config) = dev.into_parts();
+    assert_eq!(Config::default(), config);
+    i2c.done();
+}
+
+#[test]
+fn dropped_scoped_config_without_changes_writes_nothing() {
+    let expectations = [I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::TEMPERATURE],
+    )];
+    let mut dev = setup(&expectations);
+    drop(dev.scoped_config());
+    block_on(dev.set_pointer(tmp1x2::Register::Temperature)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn scoped_config_restore_without_changes_does_nothing() {
+    let mut dev = setup(&[]);
+    let guard = dev.scoped_config();
+    block_on(guard.restore()).unwrap();
+    dev.destroy().done();
+}
+
+#[cfg(not(feature = "pec"))]
+fn config_read(msb: u8, lsb: u8) -> I2cTransaction {
+    I2cTransaction::write_read(DEVICE_ADDRESS, vec![Register::CONFIG], vec![msb, lsb])
+}
+
+#[test]
+#[cfg(not(feature = "pec"))]
+fn probe_detects_tmp1x2() {
+    let expectations = [
+        config_read(DEFAULT_MSB, DEFAULT_LSB),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_LSB,
+            ],
+        ),
+        // the pointer is still set to the configuration register
+        I2cTransaction::read(
+            DEVICE_ADDRESS,
+            vec![DEFAULT_MSB | BFH::EXTENDED_MODE, DEFAULT_LSB],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    assert_eq!(DeviceKind::Tmp1x2, block_on(dev.probe()).unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+#[cfg(not(feature = "pec"))]
+fn probe_detects_unknown_device_from_resolution_bits() {
+    let expectations = [config_read(DEFAULT_MSB, 0)];
+    let mut dev = setup(&expectations);
+    assert_eq!(DeviceKind::Unknown, block_on(dev.probe()).unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+#[cfg(not(feature = "pec"))]
+fn probe_detects_unknown_device_from_extended_mode_bit() {
+    let expectations = [
+        config_read(DEFAULT_MSB, DEFAULT_LSB),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_LSB,
+            ],
+        ),
+        I2cTransaction::read(DEVICE_ADDRESS, vec![DEFAULT_MSB, DEFAULT_LSB]),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    assert_eq!(DeviceKind::Unknown, block_on(dev.probe()).unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+fn can_set_conversion_rate_4hz_after_8hz() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB | BFH::CONV_RATE0, DEFAULT_LSB],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.set_conversion_rate(CR::_8Hz)).unwrap();
+    block_on(dev.set_conversion_rate(CR::_4Hz)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn can_set_conversion_rate_and_change_into_continuous() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB & !BFH::CONV_RATE1 | BFH::CONV_RATE0,
+                DEFAULT_LSB,
+            ],
+        ),
+    ];
+    let dev = setup(&expectations);
+    let dev = block_on(dev.into_one_shot()).unwrap();
+    let dev = block_on(dev.set_conversion_rate_continuous(CR::_1Hz)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn can_write_threshold_register() {
+    let expectations = get_write_expectation(Register::T_HIGH, 0x34, 0x12);
+    let mut dev = setup(&expectations);
+    block_on(dev.write_register(tmp1x2::Register::THigh, [0x12, 0x34])).unwrap();
+    let (mut i2c, config) = dev.into_parts();
+    assert_eq!(Config::default(), config);
+    i2c.done();
+}
+
+#[test]
+fn writing_config_register_updates_cached_config() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB,
+        DEFAULT_MSB | BFH::EXTENDED_MODE,
+    );
+    let mut dev = setup(&expectations);
+    block_on(dev.write_register(
+        tmp1x2::Register::Config,
+        [DEFAULT_MSB | BFH::EXTENDED_MODE, DEFAULT_LSB],
+    ))
+    .unwrap();
+    assert_eq!((EXTENDED_MIN_C, EXTENDED_MAX_C), dev.temperature_range());
+    dev.destroy().done();
+}
+
+#[test]
+fn temperature_range_follows_measurement_mode() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB,
+        DEFAULT_MSB | BFH::EXTENDED_MODE,
+    );
+    let mut dev = setup(&expectations);
+    assert_eq!((NORMAL_MIN_C, NORMAL_MAX_C), dev.temperature_range());
+    block_on(dev.enable_extended_mode()).unwrap();
+    assert_eq!((EXTENDED_MIN_C, EXTENDED_MAX_C), dev.temperature_range());
+    dev.destroy().done();
+}
+
+#[test]
+fn can_use_exact_slave_address() {
+    let expectations = [I2cTransaction::write(
+        0x2A,
+        vec![
+            Register::CONFIG,
+            DEFAULT_MSB | BFH::EXTENDED_MODE,
+            DEFAULT_LSB,
+        ],
+    )];
+    let mut dev = Tmp1x2::new(I2cMock::new(&expectations), SlaveAddr::exact(0x2A).unwrap());
+    block_on(dev.enable_extended_mode()).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn can_replace_bus() {
+    let extended_msb = DEFAULT_MSB | BFH::EXTENDED_MODE;
+    let first = [I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::CONFIG, extended_msb, DEFAULT_LSB],
+    )];
+    let second = [I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![
+            Register::CONFIG,
+            extended_msb,
+            DEFAULT_LSB | BFL::THERMOSTAT,
+        ],
+    )];
+    let mut dev = setup(&first);
+    block_on(dev.enable_extended_mode()).unwrap();
+    let mut old = dev.replace_bus(I2cMock::new(&second));
+    old.done();
+    block_on(dev.set_thermostat_mode(TM::Interrupt)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn can_reuse_slave_address() {
+    let address = SlaveAddr::Alternative(true, false);
+    let expectations = [I2cTransaction::write(
+        DEVICE_ADDRESS | 0b10,
+        vec![
+            Register::CONFIG,
+            DEFAULT_MSB | BFH::EXTENDED_MODE,
+            DEFAULT_LSB,
+        ],
+    )];
+    let first = Tmp1x2::new(I2cMock::new(&[]), address);
+    let mut second = Tmp1x2::new(I2cMock::new(&expectations), address);
+    first.destroy().done();
+    block_on(second.enable_extended_mode()).unwrap();
+    second.destroy().done();
+}
+
+#[test]
+fn config_bytes_match_written_data() {
+    let config = Config::from_bits(
+        Config::CONV_RATE0
+            | Config::ALERT
+            | Config::RESOLUTION
+            | Config::FAULT_QUEUE0
+            | Config::FAULT_QUEUE1,
+    );
+    let [msb, lsb] = config.to_bytes();
+    let expectations = [I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::CONFIG, msb, lsb],
+    )];
+    let mut dev = setup(&expectations);
+    block_on(dev.reconfigure(config)).unwrap();
+    dev.destroy().done();
+}
+
+macro_rules! raw_threshold_test {
+    ($name:ident, $method:ident, $register:expr, $counts:expr, $msb:expr, $lsb:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = [I2cTransaction::write(
+                DEVICE_ADDRESS,
+                vec![$register, $msb, $lsb],
+            )];
+            let mut dev = setup(&expectations);
+            block_on(dev.$method($counts)).unwrap();
+            dev.destroy().done();
+        }
+    };
+}
+
+raw_threshold_test!(
+    can_set_high_threshold_raw,
+    set_high_threshold_raw,
+    Register::T_HIGH,
+    1280,
+    0b0101_0000,
+    0
+);
+raw_threshold_test!(
+    can_set_low_threshold_raw,
+    set_low_threshold_raw,
+    Register::T_LOW,
+    -4,
+    0b1111_1111,
+    0b1100_0000
+);
+raw_threshold_test!(
+    raw_threshold_is_capped,
+    set_high_threshold_raw,
+    Register::T_HIGH,
+    4095,
+    0b0111_1111,
+    0b1111_0000
+);
+
+#[test]
+fn can_set_threshold_raw_in_extended_mode() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_LSB,
+            ],
+        ),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH, 0b0100_1011, 0]),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::T_LOW, 0b1111_1111, 0b1110_0000],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.enable_extended_mode()).unwrap();
+    block_on(dev.set_high_threshold_raw(2400)).unwrap();
+    block_on(dev.set_low_threshold_raw(-4)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn deferred_config_writes_are_flushed_on_continuous() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_LSB | BFL::FAULT_QUEUE1 | BFL::THERMOSTAT,
+            ],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    dev.set_deferred_config_writes(true);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    block_on(dev.set_thermostat_mode(TM::Interrupt)).unwrap();
+    block_on(dev.set_fault_queue(FQ::_4)).unwrap();
+    block_on(dev.enable_extended_mode()).unwrap();
+    let dev = block_on(dev.into_continuous()).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn deferred_config_writes_are_flushed_on_one_shot_trigger() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_LSB | BFL::SHUTDOWN | BFL::ONE_SHOT,
+            ],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    dev.set_deferred_config_writes(true);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    block_on(dev.enable_extended_mode()).unwrap();
+    assert!(matches!(
+        block_on(dev.read_temperature()),
+        Err(Error::NotReady)
+    ));
+    dev.destroy().done();
+}
+
+#[test]
+fn config_writes_are_not_deferred_in_continuous_mode() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB | BFL::FAULT_QUEUE1,
+        DEFAULT_MSB,
+    );
+    let mut dev = setup(&expectations);
+    dev.set_deferred_config_writes(true);
+    block_on(dev.set_fault_queue(FQ::_4)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn safe_config_change_brackets_extended_mode_with_shutdown() {
+    let extended_msb = DEFAULT_MSB | BFH::EXTENDED_MODE;
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, extended_msb, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, extended_msb, DEFAULT_LSB],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    dev.set_safe_config_changes(true);
+    block_on(dev.enable_extended_mode()).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn safe_config_change_does_not_bracket_other_fields() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB | BFL::FAULT_QUEUE1,
+        DEFAULT_MSB,
+    );
+    let mut dev = setup(&expectations);
+    dev.set_safe_config_changes(true);
+    block_on(dev.set_fault_queue(FQ::_4)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn only_extended_mode_requires_shutdown() {
+    for field in ConfigField::all() {
+        assert_eq!(field == ConfigField::ExtendedMode, requires_shutdown(field));
+    }
+}
+
+#[test]
+fn can_save_and_restore_state() {
+    let extended_msb = DEFAULT_MSB | BFH::EXTENDED_MODE;
+    let config_write = I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![
+            Register::CONFIG,
+            extended_msb,
+            DEFAULT_LSB | BFL::THERMOSTAT,
+        ],
+    );
+    let t_low_write = I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::T_LOW, 0b0000_0010, 0b1000_0000],
+    );
+    let t_high_write = I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::T_HIGH, 0b0010_0101, 0b1000_0000],
+    );
+    let mut expectations = vec![
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, extended_msb, DEFAULT_LSB],
+        ),
+        config_write.clone(),
+        t_low_write.clone(),
+        t_high_write.clone(),
+    ];
+    expectations.extend([config_write, t_low_write, t_high_write]);
+    let mut dev = setup(&expectations);
+    block_on(dev.enable_extended_mode()).unwrap();
+    block_on(dev.set_thermostat_mode(TM::Interrupt)).unwrap();
+    block_on(dev.set_low_temperature_threshold(5.0)).unwrap();
+    block_on(dev.set_high_temperature_threshold(75.0)).unwrap();
+    let state = dev.save_state();
+    assert_eq!(0x0280, state.t_low_raw);
+    assert_eq!(0x2580, state.t_high_raw);
+    dev.reset_internal_driver_state();
+    assert_ne!(state, dev.save_state());
+    block_on(dev.restore_state(state)).unwrap();
+    assert_eq!(state, dev.save_state());
+    dev.destroy().done();
+}
+
+#[test]
+fn default_state_has_power_up_thresholds() {
+    let dev = setup(&[]);
+    let state = dev.save_state();
+    assert_eq!(Config::default(), state.config);
+    assert_eq!(0x4B00, state.t_low_raw);
+    assert_eq!(0x5000, state.t_high_raw);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_apply_desired_config() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_MSB & !BFH::CONV_RATE1 | BFH::CONV_RATE0 | BFH::EXTENDED_MODE,
+                DEFAULT_LSB | BFL::FAULT_QUEUE1 | BFL::ALERT_POLARITY | BFL::THERMOSTAT,
+            ],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::T_LOW, 0b1111_0011, 0b1000_0000],
+        ),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH, 0b0100_1011, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let desired = DesiredConfig {
+        conversion_rate: CR::_1Hz,
+        fault_queue: FQ::_4,
+        alert_polarity: AP::ActiveHigh,
+        thermostat_mode: TM::Interrupt,
+        extended_mode: true,
+        low_threshold: -25.0,
+        high_threshold: 150.0,
+    };
+    block_on(dev.apply(desired)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn apply_rejects_thresholds_out_of_range() {
+    let mut dev = setup(&[]);
+    let desired = DesiredConfig {
+        high_threshold: 150.0,
+        ..DesiredConfig::default()
+    };
+    match block_on(dev.apply(desired)) {
+        Err(Error::InvalidInputData) => (),
+        _ => panic!("Error::InvalidInputData not returned."),
+    }
+    dev.destroy().done();
+}
+
+#[test]
+fn can_apply_parsed_config_word() {
+    let expectations = get_write_expectation(
+        Register::CONFIG,
+        DEFAULT_LSB | BFL::THERMOSTAT,
+        DEFAULT_MSB | BFH::EXTENDED_MODE,
+    );
+    let mut dev = setup(&expectations);
+    let config = Config::parse_hex("0xB062").unwrap();
+    block_on(dev.apply_config_word(config.bits())).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn reconfigure_brackets_safe_config_changes() {
+    let extended_msb = DEFAULT_MSB | BFH::EXTENDED_MODE;
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, extended_msb, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, extended_msb, DEFAULT_LSB],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    dev.set_safe_config_changes(true);
+    let target = Config::from_bits(Config::default().bits() | Config::EXTENDED_MODE);
+    block_on(dev.reconfigure(target)).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn reconfigure_is_deferred_while_shut_down() {
+    let expectations = [I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+    )];
+    let mut dev = setup(&expectations);
+    dev.set_deferred_config_writes(true);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    let target = Config::from_bits(Config::default().bits() | Config::EXTENDED_MODE);
+    block_on(dev.reconfigure(target)).unwrap();
+    let (mut i2c, config) = dev.into_parts();
+    assert!(config.is_extended_mode());
+    i2c.done();
+}
+
+#[test]
+fn restored_config_is_deferred_while_shut_down() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_MSB, DEFAULT_LSB | BFL::SHUTDOWN],
+        ),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_LOW, 0x4B, 0]),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH, 0x50, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    dev.set_deferred_config_writes(true);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    let mut state = dev.save_state();
+    state.config = Config::from_bits(state.config.bits() | Config::EXTENDED_MODE);
+    block_on(dev.restore_state(state)).unwrap();
+    assert_eq!(state, dev.save_state());
+    dev.destroy().done();
+}
+
+macro_rules! preview_config_test {
+    ($name:ident, $change:expr, $set:expr, $cleared:expr) => {
+        #[test]
+        fn $name() {
+            let dev = setup(&[]);
+            let config = dev.preview_config_after($change);
+            let expected = (Config::default().bits() | $set) & !$cleared;
+            assert_eq!(Config::from_bits(expected), config);
+            dev.destroy().done();
+        }
+    };
+}
+
+preview_config_test!(
+    preview_conversion_rate,
+    ConfigChange::ConversionRate(CR::_1Hz),
+    Config::CONV_RATE0,
+    Config::CONV_RATE1
+);
+preview_config_test!(
+    preview_extended_mode,
+    ConfigChange::ExtendedMode(true),
+    Config::EXTENDED_MODE,
+    0
+);
+preview_config_test!(
+    preview_fault_queue,
+    ConfigChange::FaultQueue(FQ::_6),
+    Config::FAULT_QUEUE0 | Config::FAULT_QUEUE1,
+    0
+);
+preview_config_test!(
+    preview_alert_polarity,
+    ConfigChange::AlertPolarity(AP::ActiveHigh),
+    Config::ALERT_POLARITY,
+    0
+);
+preview_config_test!(
+    preview_thermostat_mode,
+    ConfigChange::ThermostatMode(TM::Interrupt),
+    Config::THERMOSTAT,
+    0
+);
+
+#[test]
+fn preview_does_not_change_cached_config() {
+    let dev = setup(&[]);
+    dev.preview_config_after(ConfigChange::ExtendedMode(true));
+    assert_eq!(Config::default(), dev.save_state().config);
     dev.destroy().done();
 }
@@ -0,0 +1,44 @@
+#![cfg(feature = "pec")]
+#![feature(async_fn_in_trait)]
+#![allow(incomplete_features)]
+extern crate embedded_hal_mock as hal;
+extern crate tmp1x2;
+use hal::i2c::Transaction as I2cTransaction;
+use tmp1x2::Error;
+
+mod common;
+use common::{block_on, setup, Register, DEVICE_ADDRESS};
+
+macro_rules! assert_near {
+    ($left:expr, $right:expr) => {
+        assert!(($left - $right) < core::f32::EPSILON && ($right - $left) < core::f32::EPSILON);
+    };
+}
+
+#[test]
+fn can_read_temperature_with_correct_pec() {
+    let expectations = [I2cTransaction::write_read(
+        DEVICE_ADDRESS,
+        vec![Register::TEMPERATURE],
+        vec![0b0110_0100, 0, 0xC6],
+    )];
+    let mut dev = setup(&expectations);
+    let value = block_on(dev.read_temperature()).unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn returns_error_for_incorrect_pec() {
+    let expectations = [I2cTransaction::write_read(
+        DEVICE_ADDRESS,
+        vec![Register::TEMPERATURE],
+        vec![0b0110_0100, 0, 0xC7],
+    )];
+    let mut dev = setup(&expectations);
+    match block_on(dev.read_temperature()) {
+        Err(Error::Pec) => (),
+        _ => panic!(),
+    }
+    dev.destroy().done();
+}
@@ -1,19 +1,70 @@
+#![feature(async_fn_in_trait)]
+#![allow(incomplete_features)]
 extern crate embedded_hal_mock as hal;
 extern crate tmp1x2;
-use hal::i2c::Transaction as I2cTransaction;
+use hal::{i2c::Transaction as I2cTransaction, MockError};
+use std::io::ErrorKind;
+use tmp1x2::{
+    marker::unit, read_all, AlertPolarity, AlertWaitError, Averager, BusTimeout, Config,
+    ConversionRate, ConversionSync, ConversionTiming, DeltaAlert, Error, Ewma, FaultQueue,
+    MedianFilter, Mode, SampleState, SlaveAddr, StaleDetector, TempUnit, Thermostat,
+    ThermostatMode, ThermostatOutput, Tmp1x2,
+};
 
 mod common;
 use common::{
-    setup, BitFlagsHigh as BFH, BitFlagsLow as BFL, Register, DEFAULT_CONFIG_LSB,
-    DEFAULT_CONFIG_MSB, DEVICE_ADDRESS,
+    block_on, poll_once_and_drop, setup, yield_now, BitFlagsHigh as BFH, BitFlagsLow as BFL,
+    DelayMock, I2cMock, MockError as BusError, PinMock, PinWait, Register, StallingI2cMock,
+    YieldingI2cMock, DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB, DEVICE_ADDRESS,
 };
 
+/// Expected register read sending the register pointer.
+///
+/// With the `pec` feature, the packet error code is appended to the data.
+fn read_expectation_at(address: u8, register: u8, data: [u8; 2]) -> I2cTransaction {
+    let mut response = data.to_vec();
+    if cfg!(feature = "pec") {
+        response.push(pec(&[
+            address << 1,
+            register,
+            (address << 1) | 1,
+            data[0],
+            data[1],
+        ]));
+    }
+    I2cTransaction::write_read(address, vec![register], response)
+}
+
+fn read_expectation(register: u8, data: [u8; 2]) -> I2cTransaction {
+    read_expectation_at(DEVICE_ADDRESS, register, data)
+}
+
+/// Expected register read while the device pointer is already set to it.
+///
+/// The pointer byte is only sent again with the `pec` feature.
+fn repeated_read_expectation(register: u8, data: [u8; 2]) -> I2cTransaction {
+    if cfg!(feature = "pec") {
+        read_expectation(register, data)
+    } else {
+        I2cTransaction::read(DEVICE_ADDRESS, data.to_vec())
+    }
+}
+
+/// SMBus packet error code: CRC-8 with polynomial 0x07.
+fn pec(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if (crc & 0x80) != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
 fn get_expectation(register: u8, lsb: u8, msb: u8) -> [I2cTransaction; 1] {
-    [I2cTransaction::write_read(
-        DEVICE_ADDRESS,
-        vec![register],
-        vec![msb, lsb],
-    )]
+    [read_expectation(register, [msb, lsb])]
 }
 
 macro_rules! read_test {
@@ -22,7 +73,7 @@ macro_rules! read_test {
         fn $name() {
             let expectations = get_expectation(Register::$register, $lsb, $msb);
             let mut dev = setup(&expectations);
-            let value = dev.$method().unwrap();
+            let value = block_on(dev.$method()).unwrap();
             assert_eq!($expected, value);
             dev.destroy().done();
         }
@@ -63,15 +114,31 @@ read_test!(
     true
 );
 
+read_test!(
+    can_read_continuous_mode,
+    read_mode,
+    CONFIG,
+    DEFAULT_CONFIG_LSB,
+    DEFAULT_CONFIG_MSB,
+    Mode::Continuous
+);
+read_test!(
+    can_read_one_shot_mode,
+    read_mode,
+    CONFIG,
+    DEFAULT_CONFIG_LSB | BFL::SHUTDOWN,
+    DEFAULT_CONFIG_MSB,
+    Mode::OneShot
+);
+
 macro_rules! assert_near {
     ($left:expr, $right:expr) => {
         assert!(($left - $right) < core::f32::EPSILON && ($right - $left) < core::f32::EPSILON);
     };
 }
 
-#[test]
-fn in_one_shot_read_temperature_triggers_measurement() {
-    let expectations = [
+fn one_shot_expectations() -> Vec<I2cTransaction> {
+    vec![
         I2cTransaction::write(
             DEVICE_ADDRESS,
             vec![
@@ -88,98 +155,1577 @@ fn in_one_shot_read_temperature_triggers_measurement() {
                 DEFAULT_CONFIG_LSB | BFL::ONE_SHOT | BFL::SHUTDOWN,
             ],
         ),
-    ];
+    ]
+}
+
+fn assert_not_ready<T, E>(result: Result<T, Error<E>>) {
+    match result {
+        Err(Error::NotReady) => (),
+        _ => panic!("Error::NotReady not returned."),
+    }
+}
+
+#[test]
+fn in_one_shot_read_temperature_triggers_measurement() {
+    let expectations = one_shot_expectations();
+    let dev = setup(&expectations);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    assert_not_ready(block_on(dev.read_temperature()));
+    dev.destroy().done();
+}
+
+#[test]
+fn in_one_shot_read_temperature_returns_not_ready_if_not_ready() {
+    let mut expectations = one_shot_expectations();
+    expectations.push(repeated_read_expectation(
+        Register::CONFIG,
+        [DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB | BFL::SHUTDOWN],
+    ));
+    let dev = setup(&expectations);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    assert_not_ready(block_on(dev.read_temperature()));
+    assert_not_ready(block_on(dev.read_temperature()));
+    dev.destroy().done();
+}
+
+#[test]
+fn one_shot_read_returns_timeout_if_timeout_completes_first() {
+    let expectations = &one_shot_expectations()[..1];
+    let dev = setup(expectations);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    let mut delay = DelayMock::default();
+    let result = block_on(dev.read_temperature_one_shot_with_timeout(
+        &mut delay,
+        ConversionTiming::Typical,
+        core::future::ready(()),
+    ));
+    match result {
+        Err(Error::Timeout) => (),
+        _ => panic!("Error::Timeout not returned."),
+    }
+    assert!(delay.delays_us.is_empty());
+    dev.destroy().done();
+}
+
+#[test]
+fn one_shot_read_returns_timeout_while_transfer_is_pending() {
+    let expectations = &one_shot_expectations()[..1];
+    let dev = Tmp1x2::new(
+        StallingI2cMock(I2cMock::new(expectations), 1),
+        SlaveAddr::default(),
+    );
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    let mut delay = DelayMock::default();
+    let result = block_on(dev.read_temperature_one_shot_with_timeout(
+        &mut delay,
+        ConversionTiming::Typical,
+        yield_now(),
+    ));
+    match result {
+        Err(Error::Timeout) => (),
+        _ => panic!("Error::Timeout not returned."),
+    }
+    assert!(delay.delays_us.is_empty());
+    dev.destroy().0.done();
+}
+
+macro_rules! one_shot_timeout_read_test {
+    ($name:ident, $timing:ident, $expected_delay_us:expr) => {
+        #[test]
+        fn $name() {
+            let mut expectations = one_shot_expectations();
+            expectations.push(repeated_read_expectation(
+                Register::CONFIG,
+                [
+                    DEFAULT_CONFIG_MSB,
+                    DEFAULT_CONFIG_LSB | BFL::SHUTDOWN | BFL::ONE_SHOT,
+                ],
+            ));
+            expectations.push(read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]));
+            let dev = setup(&expectations);
+            let mut dev = block_on(dev.into_one_shot()).unwrap();
+            let mut delay = DelayMock::default();
+            let temp = block_on(dev.read_temperature_one_shot_with_timeout(
+                &mut delay,
+                ConversionTiming::$timing,
+                core::future::pending::<()>(),
+            ))
+            .unwrap();
+            assert_near!(100.0, temp);
+            assert_eq!(vec![$expected_delay_us], delay.delays_us);
+            dev.destroy().done();
+        }
+    };
+}
+
+one_shot_timeout_read_test!(
+    one_shot_read_with_timeout_waits_typical_time,
+    Typical,
+    26_000
+);
+one_shot_timeout_read_test!(one_shot_read_with_timeout_waits_max_time, Max, 35_000);
+
+#[test]
+fn in_one_shot_can_read_temperature() {
+    let mut expectations = one_shot_expectations();
+    expectations.push(repeated_read_expectation(
+        Register::CONFIG,
+        [
+            DEFAULT_CONFIG_MSB,
+            DEFAULT_CONFIG_LSB | BFL::SHUTDOWN | BFL::ONE_SHOT,
+        ],
+    ));
+    expectations.push(read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]));
     let dev = setup(&expectations);
-    let mut dev = dev.into_one_shot().unwrap();
-    match dev.read_temperature() {
-        Err(nb::Error::WouldBlock) => (),
-        _ => panic!(),
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    assert_not_ready(block_on(dev.read_temperature()));
+    let temp = block_on(dev.read_temperature()).unwrap();
+    assert_near!(100.0, temp);
+    dev.destroy().done();
+}
+
+#[test]
+fn in_continuous_can_read_temperature() {
+    let expectations = [read_expectation(Register::TEMPERATURE, [0b0110_0100, 0])];
+    let mut dev = setup(&expectations);
+    let value = block_on(dev.read_temperature()).unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_all() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+        read_expectation_at(DEVICE_ADDRESS | 1, Register::TEMPERATURE, [0b0001_1001, 0]),
+        read_expectation_at(DEVICE_ADDRESS | 2, Register::TEMPERATURE, [0b1110_0111, 0]),
+    ];
+    let mut i2c = I2cMock::new(&expectations);
+    let addresses = [DEVICE_ADDRESS, DEVICE_ADDRESS | 1, DEVICE_ADDRESS | 2];
+    let temperatures = block_on(read_all(&mut i2c, addresses)).unwrap();
+    assert_near!(100.0, temperatures[0]);
+    assert_near!(25.0, temperatures[1]);
+    assert_near!(-25.0, temperatures[2]);
+    i2c.done();
+}
+
+#[test]
+fn read_temperature_if_new_skips_until_new_conversion() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut state = SampleState::default();
+    let value = block_on(dev.read_temperature_if_new(&mut state, 1000)).unwrap();
+    assert_near!(100.0, value.unwrap());
+    let value = block_on(dev.read_temperature_if_new(&mut state, 1010)).unwrap();
+    assert_eq!(None, value);
+    let value = block_on(dev.read_temperature_if_new(&mut state, 1250)).unwrap();
+    assert_near!(25.0, value.unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_averaged() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1010, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1011, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1100, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut averager = Averager::<3>::new();
+    for expected in &[25.0, 25.5, 26.0, 27.0] {
+        let value = block_on(averager.read_averaged(&mut dev)).unwrap();
+        assert_near!(expected, value);
+    }
+    dev.destroy().done();
+}
+
+#[test]
+fn median_filter_rejects_spike() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0b0001_0000]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut filter = MedianFilter::<3>::new();
+    for expected in &[25.0, 25.0, 25.0, 25.0625, 25.0625] {
+        let value = block_on(filter.read_temperature_median(&mut dev)).unwrap();
+        assert_near!(expected, value);
+    }
+    dev.destroy().done();
+}
+
+#[test]
+fn filters_add_calibration_offset() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    dev.set_calibration_offset(-1.5);
+    let averaged = block_on(Averager::<3>::new().read_averaged(&mut dev)).unwrap();
+    assert_near!(23.5, averaged);
+    let median = block_on(MedianFilter::<3>::new().read_temperature_median(&mut dev)).unwrap();
+    assert_near!(23.5, median);
+    let mut ewma = Ewma::new(0.5).unwrap();
+    let average = block_on(ewma.read_temperature_ewma(&mut dev)).unwrap();
+    assert_near!(23.5, average);
+    dev.destroy().done();
+}
+
+#[test]
+fn delta_alert_reports_only_large_changes() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0b1000_0000]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1010, 0b0001_0000]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut alert = DeltaAlert::new(1.0);
+    assert_eq!(Some(25.0), block_on(alert.poll(&mut dev)).unwrap());
+    assert_eq!(None, block_on(alert.poll(&mut dev)).unwrap());
+    assert_eq!(Some(26.0625), block_on(alert.poll(&mut dev)).unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+fn ewma_follows_step_input() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1110, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1110, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut ewma = Ewma::new(0.5).unwrap();
+    for expected in &[20.0, 25.0, 27.5] {
+        let value = block_on(ewma.read_temperature_ewma(&mut dev)).unwrap();
+        assert_near!(expected, value);
     }
     dev.destroy().done();
 }
 
 #[test]
-fn in_one_shot_read_temperature_returns_would_block_if_not_ready() {
+fn can_read_temperature_and_thresholds() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        read_expectation(Register::T_LOW, [0b0100_1011, 0]),
+        read_expectation(Register::T_HIGH, [0b0101_0000, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let (temperature, t_low, t_high) = block_on(dev.read_temperature_and_thresholds()).unwrap();
+    assert_near!(25.0, temperature);
+    assert_near!(75.0, t_low);
+    assert_near!(80.0, t_high);
+    dev.destroy().done();
+}
+
+macro_rules! unit_marker_read_test {
+    ($name:ident, $unit:ident, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = get_expectation(Register::TEMPERATURE, 0, 0b0001_1001);
+            let mut dev = Tmp1x2::<_, _, unit::$unit>::new_with_unit(
+                I2cMock::new(&expectations),
+                SlaveAddr::default(),
+            );
+            let temperature = block_on(dev.read_temperature()).unwrap();
+            assert_near!($expected, temperature);
+            dev.destroy().done();
+        }
+    };
+}
+
+unit_marker_read_test!(can_read_temperature_in_celsius_unit, Celsius, 25.0);
+unit_marker_read_test!(can_read_temperature_in_fahrenheit_unit, Fahrenheit, 77.0);
+unit_marker_read_test!(can_read_temperature_in_kelvin_unit, Kelvin, 298.15);
+
+#[test]
+fn unit_marker_does_not_affect_other_read_methods() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+    ];
+    let mut dev = Tmp1x2::<_, _, unit::Fahrenheit>::new_with_unit(
+        I2cMock::new(&expectations),
+        SlaveAddr::default(),
+    );
+    let kelvin = block_on(dev.read_temperature_in(TempUnit::Kelvin)).unwrap();
+    assert_near!(298.15, kelvin);
+    let celsius = block_on(dev.read_temperature_in(TempUnit::Celsius)).unwrap();
+    assert_near!(25.0, celsius);
+    let mut fahrenheit = 0.0;
+    block_on(dev.read_temperature_into(&mut fahrenheit)).unwrap();
+    assert_near!(77.0, fahrenheit);
+    dev.destroy().done();
+}
+
+type FahrenheitDevice = Tmp1x2<I2cMock, tmp1x2::marker::mode::Continuous, unit::Fahrenheit>;
+
+fn setup_fahrenheit(expectations: &[I2cTransaction]) -> FahrenheitDevice {
+    Tmp1x2::new_with_unit(I2cMock::new(expectations), SlaveAddr::default())
+}
+
+// 25°C and 30°C
+const READ_25: [u8; 2] = [0b0001_1001, 0];
+const READ_30: [u8; 2] = [0b0001_1110, 0];
+
+macro_rules! fahrenheit_read_test {
+    ($name:ident, $expectations:expr, $read:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let mut dev = setup_fahrenheit(&$expectations);
+            let read: fn(&mut FahrenheitDevice) -> f32 = $read;
+            let value = read(&mut dev);
+            assert_near!($expected, value);
+            dev.destroy().done();
+        }
+    };
+}
+
+fahrenheit_read_test!(
+    robust_read_uses_unit,
+    [
+        read_expectation(Register::TEMPERATURE, READ_25),
+        read_expectation(Register::CONFIG, [DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB]),
+    ],
+    |dev| block_on(dev.read_temperature_robust()).unwrap(),
+    77.0
+);
+fahrenheit_read_test!(
+    complete_read_uses_unit,
+    [
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::TEMPERATURE]),
+        I2cTransaction::read(DEVICE_ADDRESS, READ_25.to_vec()),
+    ],
+    |dev| {
+        block_on(dev.initiate_read()).unwrap();
+        block_on(dev.complete_read()).unwrap()
+    },
+    77.0
+);
+fahrenheit_read_test!(
+    averaged_read_uses_unit,
+    [
+        read_expectation(Register::TEMPERATURE, READ_25),
+        repeated_read_expectation(Register::TEMPERATURE, READ_30),
+    ],
+    |dev| block_on(dev.read_temperature_averaged(2, &mut DelayMock::default())).unwrap(),
+    81.5
+);
+fahrenheit_read_test!(
+    read_with_health_uses_unit,
+    [read_expectation(Register::TEMPERATURE, READ_25)],
+    |dev| {
+        let mut detector = StaleDetector::new(2);
+        block_on(dev.read_temperature_with_health(&mut detector))
+            .unwrap()
+            .0
+    },
+    77.0
+);
+fahrenheit_read_test!(
+    sign_magnitude_read_uses_unit,
+    [read_expectation(Register::TEMPERATURE, READ_25)],
+    |dev| block_on(dev.read_temperature_sign_magnitude()).unwrap().1,
+    77.0
+);
+fahrenheit_read_test!(
+    delta_read_uses_unit,
+    [
+        read_expectation(Register::TEMPERATURE, READ_25),
+        repeated_read_expectation(Register::TEMPERATURE, READ_30),
+    ],
+    |dev| {
+        block_on(dev.read_temperature_delta()).unwrap();
+        block_on(dev.read_temperature_delta()).unwrap().1
+    },
+    9.0
+);
+fahrenheit_read_test!(
+    averager_uses_unit,
+    [
+        read_expectation(Register::TEMPERATURE, READ_25),
+        repeated_read_expectation(Register::TEMPERATURE, READ_30),
+    ],
+    |dev| {
+        let mut averager = Averager::<2>::new();
+        block_on(averager.read_averaged(dev)).unwrap();
+        block_on(averager.read_averaged(dev)).unwrap()
+    },
+    81.5
+);
+fahrenheit_read_test!(
+    median_filter_uses_unit,
+    [read_expectation(Register::TEMPERATURE, READ_25)],
+    |dev| block_on(MedianFilter::<3>::new().read_temperature_median(dev)).unwrap(),
+    77.0
+);
+fahrenheit_read_test!(
+    ewma_uses_unit,
+    [read_expectation(Register::TEMPERATURE, READ_25)],
+    |dev| block_on(Ewma::new(0.5).unwrap().read_temperature_ewma(dev)).unwrap(),
+    77.0
+);
+fahrenheit_read_test!(
+    delta_alert_uses_unit,
+    [read_expectation(Register::TEMPERATURE, READ_25)],
+    |dev| block_on(DeltaAlert::new(1.0).poll(dev)).unwrap().unwrap(),
+    77.0
+);
+fahrenheit_read_test!(
+    conversion_sync_uses_unit,
+    [
+        read_expectation(
+            Register::CONFIG,
+            [DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB | BFL::ONE_SHOT],
+        ),
+        read_expectation(Register::TEMPERATURE, READ_25),
+    ],
+    |dev| block_on(ConversionSync::new().read_if_ready(dev))
+        .unwrap()
+        .unwrap(),
+    77.0
+);
+
+#[test]
+fn thresholds_are_read_in_unit() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, READ_25),
+        read_expectation(Register::T_LOW, [0b0100_1011, 0]),
+        read_expectation(Register::T_HIGH, [0b0101_0000, 0]),
+    ];
+    let mut dev = setup_fahrenheit(&expectations);
+    let (temperature, t_low, t_high) = block_on(dev.read_temperature_and_thresholds()).unwrap();
+    assert_near!(77.0, temperature);
+    assert_near!(167.0, t_low);
+    assert_near!(176.0, t_high);
+    dev.destroy().done();
+}
+
+#[test]
+fn thermostat_uses_unit() {
+    let expectations = [read_expectation(Register::TEMPERATURE, READ_25)];
+    // 25°C is above 70°F + 1°F
+    let mut thermostat = Thermostat::new(setup_fahrenheit(&expectations), 70.0, 1.0);
+    assert_eq!(
+        ThermostatOutput::Cool,
+        block_on(thermostat.update()).unwrap()
+    );
+    thermostat.destroy().destroy().done();
+}
+
+#[test]
+fn unit_is_kept_in_one_shot_mode() {
+    let config = DEFAULT_CONFIG_LSB | BFL::SHUTDOWN;
     let expectations = [
         I2cTransaction::write(
             DEVICE_ADDRESS,
-            vec![
-                Register::CONFIG,
-                DEFAULT_CONFIG_MSB,
-                DEFAULT_CONFIG_LSB | BFL::SHUTDOWN,
-            ],
+            vec![Register::CONFIG, DEFAULT_CONFIG_MSB, config],
         ),
         I2cTransaction::write(
             DEVICE_ADDRESS,
-            vec![
-                Register::CONFIG,
-                DEFAULT_CONFIG_MSB,
-                DEFAULT_CONFIG_LSB | BFL::ONE_SHOT | BFL::SHUTDOWN,
-            ],
+            vec![Register::CONFIG, DEFAULT_CONFIG_MSB, config | BFL::ONE_SHOT],
         ),
-        I2cTransaction::write_read(
-            DEVICE_ADDRESS,
-            vec![Register::CONFIG],
-            vec![DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB],
+        repeated_read_expectation(
+            Register::CONFIG,
+            [DEFAULT_CONFIG_MSB, config | BFL::ONE_SHOT],
         ),
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
     ];
-    let dev = setup(&expectations);
-    let mut dev = dev.into_one_shot().unwrap();
-    dev.read_temperature().expect_err("Should return an error");
-    match dev.read_temperature() {
-        Err(nb::Error::WouldBlock) => (),
-        _ => panic!(),
-    }
+    let dev: Tmp1x2<_, _, unit::Fahrenheit> =
+        Tmp1x2::new_with_unit(I2cMock::new(&expectations), SlaveAddr::default());
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    assert!(matches!(
+        block_on(dev.read_temperature()),
+        Err(Error::NotReady)
+    ));
+    let temperature = block_on(dev.read_temperature()).unwrap();
+    assert_near!(77.0, temperature);
     dev.destroy().done();
 }
 
 #[test]
-fn in_one_shot_can_read_temperature() {
+fn can_read_temperature_split() {
+    let expectations = [
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::TEMPERATURE]),
+        I2cTransaction::read(DEVICE_ADDRESS, vec![0b0001_1001, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let temperature = block_on(dev.read_temperature_split()).unwrap();
+    assert_near!(25.0, temperature);
+    dev.destroy().done();
+}
+
+macro_rules! within_window_test {
+    ($name:ident, $msb:expr, $lsb:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            // default thresholds: 75°C and 80°C
+            let expectations = get_expectation(Register::TEMPERATURE, $lsb, $msb);
+            let mut dev = setup(&expectations);
+            assert_eq!($expected, block_on(dev.is_within_window()).unwrap());
+            dev.destroy().done();
+        }
+    };
+}
+
+within_window_test!(within_window_inside, 77, 0, true);
+within_window_test!(within_window_at_low_threshold, 75, 0, true);
+within_window_test!(within_window_at_high_threshold, 80, 0, true);
+within_window_test!(within_window_below, 74, 0b1111_0000, false);
+within_window_test!(within_window_above, 80, 0b0001_0000, false);
+within_window_test!(within_window_negative, 0b1111_1111, 0, false);
+
+#[test]
+fn within_window_uses_written_thresholds() {
+    let expectations = [
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_LOW, 0b0001_0100, 0]),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH, 0b0001_1110, 0]),
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0100_1101, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.set_low_temperature_threshold(20.0)).unwrap();
+    block_on(dev.set_high_temperature_threshold(30.0)).unwrap();
+    assert!(block_on(dev.is_within_window()).unwrap());
+    assert!(!block_on(dev.is_within_window()).unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+fn within_window_ignores_extended_mode_flag() {
     let expectations = [
         I2cTransaction::write(
             DEVICE_ADDRESS,
             vec![
                 Register::CONFIG,
-                DEFAULT_CONFIG_MSB,
-                DEFAULT_CONFIG_LSB | BFL::SHUTDOWN,
+                DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_CONFIG_LSB,
             ],
         ),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_LOW, 0, 0]),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH, 0b0011_0010, 0]),
+        // 100°C with the extended mode flag set
+        read_expectation(Register::TEMPERATURE, [0b0011_0010, 0b0000_0001]),
+        // 100.125°C
+        repeated_read_expectation(Register::TEMPERATURE, [0b0011_0010, 0b0000_1001]),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.enable_extended_mode()).unwrap();
+    block_on(dev.set_low_temperature_threshold(0.0)).unwrap();
+    block_on(dev.set_high_temperature_threshold(100.0)).unwrap();
+    assert!(block_on(dev.is_within_window()).unwrap());
+    assert!(!block_on(dev.is_within_window()).unwrap());
+    dev.destroy().done();
+}
+
+#[test]
+fn dropped_read_does_not_leave_pointer_cached() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        read_expectation(Register::TEMPERATURE, [0b0001_1010, 0]),
+    ];
+    let mut dev = Tmp1x2::new(
+        YieldingI2cMock(I2cMock::new(&expectations)),
+        SlaveAddr::default(),
+    );
+    assert!(!poll_once_and_drop(dev.read_temperature()));
+    let temperature = block_on(dev.read_temperature()).unwrap();
+    assert_near!(26.0, temperature);
+    dev.destroy().0.done();
+}
+
+#[test]
+fn dropped_one_shot_trigger_is_repeated() {
+    let config = DEFAULT_CONFIG_LSB | BFL::SHUTDOWN;
+    let expectations = [
         I2cTransaction::write(
             DEVICE_ADDRESS,
-            vec![
-                Register::CONFIG,
-                DEFAULT_CONFIG_MSB,
-                DEFAULT_CONFIG_LSB | BFL::ONE_SHOT | BFL::SHUTDOWN,
-            ],
+            vec![Register::CONFIG, DEFAULT_CONFIG_MSB, config],
         ),
-        I2cTransaction::write_read(
+        I2cTransaction::write(
             DEVICE_ADDRESS,
-            vec![Register::CONFIG],
-            vec![DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB | BFL::ONE_SHOT],
+            vec![Register::CONFIG, DEFAULT_CONFIG_MSB, config | BFL::ONE_SHOT],
         ),
-        I2cTransaction::write_read(
+        I2cTransaction::write(
             DEVICE_ADDRESS,
-            vec![Register::TEMPERATURE],
-            vec![0b0110_0100, 0],
+            vec![Register::CONFIG, DEFAULT_CONFIG_MSB, config | BFL::ONE_SHOT],
+        ),
+        repeated_read_expectation(
+            Register::CONFIG,
+            [DEFAULT_CONFIG_MSB, config | BFL::ONE_SHOT],
         ),
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
     ];
-    let dev = setup(&expectations);
-    let mut dev = dev.into_one_shot().unwrap();
-    dev.read_temperature().expect_err("Should return an error");
-    let temp = dev.read_temperature().unwrap();
-    assert_near!(100.0, temp);
+    let dev = Tmp1x2::new(
+        YieldingI2cMock(I2cMock::new(&expectations)),
+        SlaveAddr::default(),
+    );
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    assert!(!poll_once_and_drop(dev.read_temperature()));
+    assert!(matches!(
+        block_on(dev.read_temperature()),
+        Err(Error::NotReady)
+    ));
+    let temperature = block_on(dev.read_temperature()).unwrap();
+    assert_near!(25.0, temperature);
+    dev.destroy().0.done();
+}
+
+#[test]
+fn conversion_sync_reads_only_ready_conversions() {
+    let expectations = [
+        read_expectation(Register::CONFIG, [DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB]),
+        repeated_read_expectation(
+            Register::CONFIG,
+            [DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB | BFL::ONE_SHOT],
+        ),
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut sync = ConversionSync::new();
+    assert_eq!(None, block_on(sync.read_if_ready(&mut dev)).unwrap());
+    assert_eq!(0, sync.conversions());
+    assert_eq!(Some(25.0), block_on(sync.read_if_ready(&mut dev)).unwrap());
+    assert_eq!(1, sync.conversions());
     dev.destroy().done();
 }
 
 #[test]
-fn in_continuous_can_read_temperature() {
-    let expectations = [I2cTransaction::write_read(
-        DEVICE_ADDRESS,
-        vec![Register::TEMPERATURE],
-        vec![0b0110_0100, 0],
-    )];
+fn conversion_sync_waits_for_ready_bit_to_clear() {
+    let ready = [DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB | BFL::ONE_SHOT];
+    let expectations = [
+        read_expectation(Register::CONFIG, ready),
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        read_expectation(Register::CONFIG, ready),
+        repeated_read_expectation(Register::CONFIG, [DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB]),
+        repeated_read_expectation(Register::CONFIG, ready),
+        read_expectation(Register::TEMPERATURE, [0b0001_1010, 0]),
+    ];
     let mut dev = setup(&expectations);
-    let value = dev.read_temperature().unwrap();
-    assert_near!(100.0, value);
+    let mut sync = ConversionSync::new();
+    assert_eq!(Some(25.0), block_on(sync.read_if_ready(&mut dev)).unwrap());
+    assert_eq!(None, block_on(sync.read_if_ready(&mut dev)).unwrap());
+    assert_eq!(None, block_on(sync.read_if_ready(&mut dev)).unwrap());
+    assert_eq!(Some(26.0), block_on(sync.read_if_ready(&mut dev)).unwrap());
+    assert_eq!(2, sync.conversions());
     dev.destroy().done();
 }
+
+#[test]
+fn flush_waits_and_discards_sample() {
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_CONFIG_MSB,
+                DEFAULT_CONFIG_LSB | BFL::SHUTDOWN,
+            ],
+        ),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB],
+        ),
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+    ];
+    let dev = setup(&expectations);
+    let dev = block_on(dev.into_one_shot()).unwrap();
+    let mut dev = block_on(dev.into_continuous()).unwrap();
+    let mut delay = DelayMock::default();
+    block_on(dev.flush(&mut delay)).unwrap();
+    assert_eq!(vec![250_000], delay.delays_us);
+    let value = block_on(dev.read_temperature()).unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn wait_for_one_shot_measurement_returns_when_ready() {
+    let not_ready = DEFAULT_CONFIG_LSB | BFL::SHUTDOWN;
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_CONFIG_MSB, not_ready],
+        ),
+        repeated_read_expectation(Register::CONFIG, [DEFAULT_CONFIG_MSB, not_ready]),
+        repeated_read_expectation(
+            Register::CONFIG,
+            [DEFAULT_CONFIG_MSB, not_ready | BFL::ONE_SHOT],
+        ),
+    ];
+    let dev = setup(&expectations);
+    let mut dev = block_on(dev.into_one_shot()).unwrap();
+    let mut delay = DelayMock::default();
+    let is_ready =
+        block_on(dev.wait_for_one_shot_measurement(&mut delay, ConversionTiming::Typical));
+    assert!(is_ready.unwrap());
+    assert_eq!(vec![26_000, 5_000], delay.delays_us);
+    dev.destroy().done();
+}
+
+macro_rules! wait_for_one_shot_timeout_test {
+    ($name:ident, $timing:ident, $polls:expr, $expected_delays_us:expr) => {
+        #[test]
+        fn $name() {
+            let not_ready = DEFAULT_CONFIG_LSB | BFL::SHUTDOWN;
+            let mut expectations = vec![I2cTransaction::write(
+                DEVICE_ADDRESS,
+                vec![Register::CONFIG, DEFAULT_CONFIG_MSB, not_ready],
+            )];
+            for _ in 0..$polls {
+                expectations.push(repeated_read_expectation(
+                    Register::CONFIG,
+                    [DEFAULT_CONFIG_MSB, not_ready],
+                ));
+            }
+            let dev = setup(&expectations);
+            let mut dev = block_on(dev.into_one_shot()).unwrap();
+            let mut delay = DelayMock::default();
+            let is_ready =
+                block_on(dev.wait_for_one_shot_measurement(&mut delay, ConversionTiming::$timing));
+            assert!(!is_ready.unwrap());
+            assert_eq!($expected_delays_us, delay.delays_us);
+            dev.destroy().done();
+        }
+    };
+}
+
+wait_for_one_shot_timeout_test!(
+    wait_for_one_shot_measurement_times_out_typical,
+    Typical,
+    3,
+    vec![26_000, 5_000, 5_000]
+);
+wait_for_one_shot_timeout_test!(
+    wait_for_one_shot_measurement_times_out_max,
+    Max,
+    1,
+    vec![35_000]
+);
+
+macro_rules! one_shot_ready_test {
+    ($name:ident, $config_lsb:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = [
+                I2cTransaction::write(
+                    DEVICE_ADDRESS,
+                    vec![
+                        Register::CONFIG,
+                        DEFAULT_CONFIG_MSB,
+                        DEFAULT_CONFIG_LSB | BFL::SHUTDOWN,
+                    ],
+                ),
+                repeated_read_expectation(Register::CONFIG, [DEFAULT_CONFIG_MSB, $config_lsb]),
+            ];
+            let dev = setup(&expectations);
+            let mut dev = block_on(dev.into_one_shot()).unwrap();
+            assert_eq!($expected, block_on(dev.is_conversion_ready()).unwrap());
+            dev.destroy().done();
+        }
+    };
+}
+
+one_shot_ready_test!(
+    one_shot_measurement_is_ready,
+    DEFAULT_CONFIG_LSB | BFL::SHUTDOWN | BFL::ONE_SHOT,
+    true
+);
+one_shot_ready_test!(
+    one_shot_measurement_is_not_ready,
+    DEFAULT_CONFIG_LSB | BFL::SHUTDOWN,
+    false
+);
+
+macro_rules! continuous_ready_test {
+    ($name:ident, $config_lsb:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = [read_expectation(
+                Register::CONFIG,
+                [DEFAULT_CONFIG_MSB, $config_lsb],
+            )];
+            let mut dev = setup(&expectations);
+            assert_eq!($expected, block_on(dev.is_conversion_ready()).unwrap());
+            dev.destroy().done();
+        }
+    };
+}
+
+continuous_ready_test!(
+    continuous_conversion_is_ready,
+    DEFAULT_CONFIG_LSB | BFL::ONE_SHOT,
+    true
+);
+continuous_ready_test!(
+    continuous_conversion_is_not_ready,
+    DEFAULT_CONFIG_LSB,
+    false
+);
+
+#[test]
+fn conversion_ready_poll_only_reads_config() {
+    let config_lsb = DEFAULT_CONFIG_LSB | BFL::THERMOSTAT;
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, DEFAULT_CONFIG_MSB, config_lsb],
+        ),
+        // active alert, which the device clears on this read in interrupt mode
+        repeated_read_expectation(
+            Register::CONFIG,
+            [DEFAULT_CONFIG_MSB & !BFH::ALERT, config_lsb | BFL::ONE_SHOT],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.set_thermostat_mode(ThermostatMode::Interrupt)).unwrap();
+    assert!(block_on(dev.is_conversion_ready()).unwrap());
+    dev.destroy().done();
+}
+
+macro_rules! read_scaled_test {
+    ($name:ident, $scale:expr, $offset:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = get_expectation(Register::TEMPERATURE, 0, 0b0110_0100);
+            let mut dev = setup(&expectations);
+            let value = block_on(dev.read_temperature_scaled($scale, $offset)).unwrap();
+            assert_near!($expected, value);
+            dev.destroy().done();
+        }
+    };
+}
+
+read_scaled_test!(can_read_temperature_fahrenheit, 1.8, 32.0, 212.0);
+read_scaled_test!(can_read_temperature_identity_scale, 1.0, 0.0, 100.0);
+
+#[test]
+fn calibration_offset_is_applied_and_removable() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    assert_near!(0.0, dev.calibration_offset());
+    dev.set_calibration_offset(-1.5);
+    assert_near!(-1.5, dev.calibration_offset());
+    let value = block_on(dev.read_temperature()).unwrap();
+    assert_near!(98.5, value);
+    dev.clear_calibration_offset();
+    assert_near!(0.0, dev.calibration_offset());
+    let value = block_on(dev.read_temperature()).unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn repeated_read_omits_pointer_when_already_set() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_CONFIG_LSB,
+            ],
+        ),
+        read_expectation(Register::TEMPERATURE, [0b0011_0010, 1]),
+    ];
+    let mut dev = setup(&expectations);
+    let value = block_on(dev.read_temperature()).unwrap();
+    assert_near!(100.0, value);
+    let value = block_on(dev.read_temperature()).unwrap();
+    assert_near!(25.0, value);
+    block_on(dev.enable_extended_mode()).unwrap();
+    let value = block_on(dev.read_temperature()).unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn read_temperature_fast_omits_pointer_when_already_set() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![
+                Register::CONFIG,
+                DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE,
+                DEFAULT_CONFIG_LSB,
+            ],
+        ),
+        read_expectation(Register::TEMPERATURE, [0b0011_0010, 1]),
+    ];
+    let mut dev = setup(&expectations);
+    let value = block_on(dev.read_temperature_fast()).unwrap();
+    assert_near!(100.0, value);
+    let value = block_on(dev.read_temperature_fast()).unwrap();
+    assert_near!(25.0, value);
+    block_on(dev.enable_extended_mode()).unwrap();
+    let value = block_on(dev.read_temperature_fast()).unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
+
+fn failed_temperature_read() -> I2cTransaction {
+    read_expectation(Register::TEMPERATURE, [0, 0]).with_error(MockError::Io(ErrorKind::Other))
+}
+
+#[test]
+fn read_temperature_retry_recovers_from_nack() {
+    let expectations = [
+        failed_temperature_read(),
+        read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let value = block_on(dev.read_temperature_retry(1)).unwrap();
+    assert_near!(100.0, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn read_temperature_retry_returns_error_after_retries() {
+    let expectations = [failed_temperature_read(), failed_temperature_read()];
+    let mut dev = setup(&expectations);
+    match block_on(dev.read_temperature_retry(1)) {
+        Err(Error::I2C(_)) => (),
+        _ => panic!("Should return an I2C error"),
+    }
+    dev.destroy().done();
+}
+
+macro_rules! read_register_test {
+    ($name:ident, $register:ident, $address:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = get_expectation($address, 0x12, 0x34);
+            let mut dev = setup(&expectations);
+            let data = block_on(dev.read_register(tmp1x2::Register::$register)).unwrap();
+            assert_eq!([0x34, 0x12], data);
+            dev.destroy().done();
+        }
+    };
+}
+
+read_register_test!(
+    can_read_temperature_register,
+    Temperature,
+    Register::TEMPERATURE
+);
+read_register_test!(can_read_config_register, Config, Register::CONFIG);
+read_register_test!(can_read_t_low_register, TLow, Register::T_LOW);
+read_register_test!(can_read_t_high_register, THigh, Register::T_HIGH);
+
+macro_rules! read_as_test {
+    ($name:ident, $type:ty, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = get_expectation(Register::TEMPERATURE, 0b1100_0000, 0b1110_0111);
+            let mut dev = setup(&expectations);
+            let value = block_on(dev.read_as::<$type>()).unwrap();
+            assert_eq!($expected, value);
+            dev.destroy().done();
+        }
+    };
+}
+
+read_as_test!(can_read_as_celsius, f32, -24.25);
+read_as_test!(can_read_as_raw, i16, -388);
+read_as_test!(can_read_as_millicelsius, i32, -24250);
+
+macro_rules! wait_for_alert_test {
+    ($name:ident, $polarity:ident, $expected:ident) => {
+        #[test]
+        fn $name() {
+            let mut dev = setup(&[]);
+            let mut pin = PinMock::default();
+            block_on(dev.wait_for_alert(&mut pin, AlertPolarity::$polarity)).unwrap();
+            assert_eq!(vec![PinWait::$expected], pin.waits);
+            dev.destroy().done();
+        }
+    };
+}
+
+wait_for_alert_test!(waits_for_rising_edge_alert, ActiveHigh, RisingEdge);
+wait_for_alert_test!(waits_for_falling_edge_alert, ActiveLow, FallingEdge);
+
+fn alert_temperature_reads(msbs: &[u8]) -> Vec<I2cTransaction> {
+    msbs.iter()
+        .enumerate()
+        .map(|(i, msb)| {
+            if i == 0 {
+                read_expectation(Register::TEMPERATURE, [*msb, 0])
+            } else {
+                repeated_read_expectation(Register::TEMPERATURE, [*msb, 0])
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn unmasked_alert_does_not_access_device() {
+    let mut dev = setup(&[]);
+    let mut pin = PinMock::default();
+    block_on(dev.wait_for_unmasked_alert(&mut pin, AlertPolarity::ActiveLow)).unwrap();
+    assert_eq!(vec![PinWait::FallingEdge], pin.waits);
+    dev.destroy().done();
+}
+
+#[test]
+fn alert_on_high_only_ignores_low_alerts() {
+    // 70°C then 85°C with the default thresholds of 75°C and 80°C
+    let mut dev = setup(&alert_temperature_reads(&[70, 85]));
+    dev.set_alert_on_low(false);
+    let mut pin = PinMock::default();
+    block_on(dev.wait_for_unmasked_alert(&mut pin, AlertPolarity::ActiveHigh)).unwrap();
+    assert_eq!(vec![PinWait::RisingEdge; 2], pin.waits);
+    dev.destroy().done();
+}
+
+#[test]
+fn alert_on_low_only_ignores_high_alerts() {
+    let mut dev = setup(&alert_temperature_reads(&[80, 85, 70]));
+    dev.set_alert_on_high(false);
+    let mut pin = PinMock::default();
+    block_on(dev.wait_for_unmasked_alert(&mut pin, AlertPolarity::ActiveHigh)).unwrap();
+    assert_eq!(vec![PinWait::RisingEdge; 3], pin.waits);
+    dev.destroy().done();
+}
+
+#[test]
+fn fully_masked_alert_waits_until_error() {
+    let mut expectations = alert_temperature_reads(&[70, 85]);
+    expectations.push(
+        repeated_read_expectation(Register::TEMPERATURE, [0, 0])
+            .with_error(MockError::Io(ErrorKind::Other)),
+    );
+    let mut dev = setup(&expectations);
+    dev.set_alert_on_high(false);
+    dev.set_alert_on_low(false);
+    let mut pin = PinMock::default();
+    let result = block_on(dev.wait_for_unmasked_alert(&mut pin, AlertPolarity::ActiveHigh));
+    assert!(matches!(
+        result,
+        Err(AlertWaitError::Device(Error::I2C(BusError(MockError::Io(
+            ErrorKind::Other
+        )))))
+    ));
+    assert_eq!(vec![PinWait::RisingEdge; 3], pin.waits);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_dump_registers() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        read_expectation(Register::CONFIG, [DEFAULT_CONFIG_MSB, DEFAULT_CONFIG_LSB]),
+        read_expectation(Register::T_LOW, [0b0100_1011, 0]),
+        read_expectation(Register::T_HIGH, [0b0101_0000, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let dump = block_on(dev.dump()).unwrap();
+    assert_eq!(0b0001_1001_0000_0000, dump.temperature_raw);
+    assert_eq!(0b1010_0000_0110_0000, dump.config_raw);
+    assert_eq!(0b0100_1011_0000_0000, dump.t_low_raw);
+    assert_eq!(0b0101_0000_0000_0000, dump.t_high_raw);
+    assert_near!(25.0, dump.temperature);
+    assert_eq!(Config::default(), dump.config);
+    assert_eq!(ConversionRate::_4Hz, dump.config.conversion_rate());
+    assert_near!(75.0, dump.t_low);
+    assert_near!(80.0, dump.t_high);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_temperature_averaged() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1010, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0b1000_0000]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut delay = DelayMock::default();
+    let value = block_on(dev.read_temperature_averaged(3, &mut delay)).unwrap();
+    assert_near!(25.5, value);
+    assert_eq!(vec![250_000, 250_000], delay.delays_us);
+    dev.destroy().done();
+}
+
+#[test]
+fn cannot_average_zero_samples() {
+    let mut dev = setup(&[]);
+    let mut delay = DelayMock::default();
+    match block_on(dev.read_temperature_averaged(0, &mut delay)) {
+        Err(Error::InvalidInputData) => (),
+        _ => panic!("Error::InvalidInputData not returned."),
+    }
+    assert!(delay.delays_us.is_empty());
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_temperature_with_health() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0b0001_0000]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut detector = StaleDetector::new(2);
+    for &(expected, expected_stale) in &[(25.0, false), (25.0, false), (25.0, true)] {
+        let (temperature, is_stale) =
+            block_on(dev.read_temperature_with_health(&mut detector)).unwrap();
+        assert_near!(expected, temperature);
+        assert_eq!(expected_stale, is_stale);
+    }
+    let (temperature, is_stale) =
+        block_on(dev.read_temperature_with_health(&mut detector)).unwrap();
+    assert_near!(25.0625, temperature);
+    assert!(!is_stale);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_temperature_into() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0])
+            .with_error(MockError::Io(ErrorKind::Other)),
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut temperature = -1.0;
+    block_on(dev.read_temperature_into(&mut temperature)).unwrap_err();
+    assert_near!(-1.0, temperature);
+    block_on(dev.read_temperature_into(&mut temperature)).unwrap();
+    assert_near!(25.0, temperature);
+    dev.destroy().done();
+}
+
+impl BusTimeout for BusError {
+    fn is_timeout(&self) -> bool {
+        matches!(self.0, MockError::Io(ErrorKind::TimedOut))
+    }
+}
+
+#[test]
+fn can_detect_bus_timeout() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0])
+            .with_error(MockError::Io(ErrorKind::TimedOut)),
+        read_expectation(Register::TEMPERATURE, [0b0001_1001, 0])
+            .with_error(MockError::Io(ErrorKind::Other)),
+    ];
+    let mut dev = setup(&expectations);
+    match block_on(dev.read_temperature()).map_err(Error::detect_timeout) {
+        Err(Error::Timeout) => (),
+        _ => panic!("Error::Timeout not returned."),
+    }
+    match block_on(dev.read_temperature()).map_err(Error::detect_timeout) {
+        Err(Error::I2C(_)) => (),
+        _ => panic!("Error::I2C not returned."),
+    }
+    dev.destroy().done();
+}
+
+#[test]
+fn can_convert_bus_timeout() {
+    let error: Error<BusError> = BusError(MockError::Io(ErrorKind::TimedOut)).into();
+    match error {
+        Error::Timeout => (),
+        _ => panic!("Error::Timeout not returned."),
+    }
+}
+
+#[test]
+fn can_read_temperature_delta() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_0010, 0b1000_0000]),
+    ];
+    let mut dev = setup(&expectations);
+    let (temperature, delta) = block_on(dev.read_temperature_delta()).unwrap();
+    assert_near!(temperature, 20.0);
+    assert_near!(delta, 0.0);
+    let (temperature, delta) = block_on(dev.read_temperature_delta()).unwrap();
+    assert_near!(temperature, 18.5);
+    assert_near!(delta, -1.5);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_set_pointer_and_read_current_register() {
+    let expectations = [
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::T_HIGH]),
+        I2cTransaction::read(DEVICE_ADDRESS, vec![0x12, 0x34]),
+        I2cTransaction::read(DEVICE_ADDRESS, vec![0x56, 0x78]),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.set_pointer(tmp1x2::Register::THigh)).unwrap();
+    let mut data = [0; 2];
+    block_on(dev.read_current_register(&mut data)).unwrap();
+    assert_eq!([0x12, 0x34], data);
+    block_on(dev.read_current_register(&mut data)).unwrap();
+    assert_eq!([0x56, 0x78], data);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_temperature_in_two_phases() {
+    let expectations = [
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::TEMPERATURE]),
+        I2cTransaction::read(DEVICE_ADDRESS, vec![0b0110_0100, 0]),
+        I2cTransaction::read(DEVICE_ADDRESS, vec![0b0001_1001, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.initiate_read()).unwrap();
+    let first = block_on(dev.complete_read()).unwrap();
+    assert_near!(100.0, first);
+    let second = block_on(dev.complete_read()).unwrap();
+    assert_near!(25.0, second);
+    dev.destroy().done();
+}
+
+#[test]
+fn set_pointer_is_reused_by_read_register() {
+    let expectations = [
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::CONFIG]),
+        repeated_read_expectation(Register::CONFIG, [0x12, 0x34]),
+    ];
+    let mut dev = setup(&expectations);
+    block_on(dev.set_pointer(tmp1x2::Register::Config)).unwrap();
+    let data = block_on(dev.read_register(tmp1x2::Register::Config)).unwrap();
+    assert_eq!([0x12, 0x34], data);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_temperature_register_raw() {
+    let expectations = get_expectation(Register::TEMPERATURE, 0b1001_0000, 0b1110_0111);
+    let mut dev = setup(&expectations);
+    let value = block_on(dev.read_temperature_register_raw()).unwrap();
+    assert_eq!(0b1110_0111_1001_0000, value);
+    dev.destroy().done();
+}
+
+#[test]
+fn thermostat_follows_temperature_sequence() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0001_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_0010, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_0011, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_0110, 0]),
+    ];
+    let mut thermostat = Thermostat::new(setup(&expectations), 20.0, 1.0);
+    for expected in &[
+        ThermostatOutput::Idle,
+        ThermostatOutput::Heat,
+        ThermostatOutput::Heat,
+        ThermostatOutput::Idle,
+        ThermostatOutput::Cool,
+    ] {
+        assert_eq!(*expected, block_on(thermostat.update()).unwrap());
+        assert_eq!(*expected, thermostat.output());
+    }
+    thermostat.destroy().destroy().done();
+}
+
+#[test]
+fn verify_fixed_bits_succeeds_if_matching() {
+    let expectations = get_expectation(Register::CONFIG, DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB);
+    let mut dev = setup(&expectations);
+    block_on(dev.verify_fixed_bits()).unwrap();
+    dev.destroy().done();
+}
+
+#[test]
+fn verify_fixed_bits_fails_if_not_matching() {
+    let expectations = get_expectation(
+        Register::CONFIG,
+        DEFAULT_CONFIG_LSB & !BFL::RESOLUTION,
+        DEFAULT_CONFIG_MSB,
+    );
+    let mut dev = setup(&expectations);
+    match block_on(dev.verify_fixed_bits()) {
+        Err(Error::UnexpectedDevice) => (),
+        _ => panic!("Should return Error::UnexpectedDevice"),
+    }
+    dev.destroy().done();
+}
+
+#[test]
+fn sample_counter_increments_across_reads() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [0b0001_1001, 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let (value, count) = block_on(dev.read_temperature_counted()).unwrap();
+    assert_near!(100.0, value);
+    assert_eq!(1, count);
+    block_on(dev.read_temperature()).unwrap();
+    let (value, count) = block_on(dev.read_temperature_counted()).unwrap();
+    assert_near!(25.0, value);
+    assert_eq!(3, count);
+    dev.destroy().done();
+}
+
+#[test]
+fn can_read_into_ring() {
+    let samples = [0b0110_0100, 0b0001_1001, 0b0000_0001];
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [samples[0], 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [samples[1], 0]),
+        repeated_read_expectation(Register::TEMPERATURE, [samples[2], 0]),
+    ];
+    let mut dev = setup(&expectations);
+    let mut ring = [0; 2];
+    let mut idx = 0;
+    block_on(dev.read_into_ring(&mut ring, &mut idx)).unwrap();
+    assert_eq!([1600, 0], ring);
+    assert_eq!(1, idx);
+    block_on(dev.read_into_ring(&mut ring, &mut idx)).unwrap();
+    assert_eq!([1600, 400], ring);
+    assert_eq!(0, idx);
+    block_on(dev.read_into_ring(&mut ring, &mut idx)).unwrap();
+    assert_eq!([16, 400], ring);
+    assert_eq!(1, idx);
+    dev.destroy().done();
+}
+
+#[test]
+fn cannot_read_into_empty_ring() {
+    let mut dev = setup(&[]);
+    let mut idx = 0;
+    match block_on(dev.read_into_ring(&mut [], &mut idx)) {
+        Err(Error::InvalidInputData) => (),
+        _ => panic!("Error::InvalidInputData not returned."),
+    }
+    dev.destroy().done();
+}
+
+#[test]
+fn out_of_bounds_ring_index_wraps_around() {
+    let expectations = [read_expectation(Register::TEMPERATURE, [0b0110_0100, 0])];
+    let mut dev = setup(&expectations);
+    let mut ring = [0; 2];
+    let mut idx = 5;
+    block_on(dev.read_into_ring(&mut ring, &mut idx)).unwrap();
+    assert_eq!([0, 1600], ring);
+    assert_eq!(0, idx);
+    dev.destroy().done();
+}
+
+macro_rules! clear_alert_test {
+    ($name:ident, $config_lsb:expr, $config_msb:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = [
+                read_expectation(Register::TEMPERATURE, [0b0110_0100, 0]),
+                read_expectation(Register::CONFIG, [$config_msb, $config_lsb]),
+            ];
+            let mut dev = setup(&expectations);
+            let (value, alert) = block_on(dev.read_temperature_and_clear_alert()).unwrap();
+            assert_near!(100.0, value);
+            assert_eq!($expected, alert);
+            dev.destroy().done();
+        }
+    };
+}
+
+clear_alert_test!(
+    read_and_clear_alert_pending,
+    DEFAULT_CONFIG_LSB | BFL::THERMOSTAT,
+    DEFAULT_CONFIG_MSB & !BFH::ALERT,
+    true
+);
+clear_alert_test!(
+    read_and_clear_alert_not_pending,
+    DEFAULT_CONFIG_LSB | BFL::THERMOSTAT,
+    DEFAULT_CONFIG_MSB | BFH::ALERT,
+    false
+);
+
+#[test]
+fn read_temperature_robust_decodes_with_device_extended_mode() {
+    let expectations = [
+        read_expectation(Register::TEMPERATURE, [0b0011_0010, 0]),
+        read_expectation(
+            Register::CONFIG,
+            [DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE, DEFAULT_CONFIG_LSB],
+        ),
+    ];
+    let mut dev = setup(&expectations);
+    let temperature = block_on(dev.read_temperature_robust()).unwrap();
+    assert_near!(100.0, temperature);
+    dev.destroy().done();
+}
+
+macro_rules! read_rounded_test {
+    ($name:ident, $digits:expr, $lsb:expr, $msb:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let mut dev = setup(&get_expectation(Register::TEMPERATURE, $lsb, $msb));
+            let temperature = block_on(dev.read_temperature_rounded($digits)).unwrap();
+            assert_near!($expected, temperature);
+            dev.destroy().done();
+        }
+    };
+}
+
+read_rounded_test!(can_read_rounded_1_digit, 1, 0b0001_0000, 0b0001_1001, 25.1);
+read_rounded_test!(
+    can_read_rounded_2_digits,
+    2,
+    0b0001_0000,
+    0b0001_1001,
+    25.06
+);
+read_rounded_test!(
+    can_read_negative_rounded_1_digit,
+    1,
+    0b1111_0000,
+    0b1111_0101,
+    -10.1
+);
+read_rounded_test!(
+    can_read_negative_rounded_2_digits,
+    2,
+    0b1111_0000,
+    0b1111_0101,
+    -10.06
+);
+
+macro_rules! read_q12_4_test {
+    ($name:ident, $lsb:expr, $msb:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let mut dev = setup(&get_expectation(Register::TEMPERATURE, $lsb, $msb));
+            assert_eq!($expected, block_on(dev.read_temperature_q12_4()).unwrap());
+            dev.destroy().done();
+        }
+    };
+}
+
+read_q12_4_test!(can_read_q12_4_25, 0, 0b0001_1001, 0x0190);
+read_q12_4_test!(can_read_q12_4_minus_10, 0, 0b1111_0110, -0x00A0);
+read_q12_4_test!(
+    can_read_q12_4_extended_mode,
+    0b1000_0001,
+    0b0000_1100,
+    0x0190
+);
+
+macro_rules! read_in_unit_test {
+    ($name:ident, $unit:ident, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let mut dev = setup(&get_expectation(Register::TEMPERATURE, 0, 0b0110_0100));
+            let temperature = block_on(dev.read_temperature_in(TempUnit::$unit)).unwrap();
+            assert_near!($expected, temperature);
+            dev.destroy().done();
+        }
+    };
+}
+
+read_in_unit_test!(can_read_in_celsius, Celsius, 100.0);
+read_in_unit_test!(can_read_in_fahrenheit, Fahrenheit, 212.0);
+read_in_unit_test!(can_read_in_kelvin, Kelvin, 373.15);
+
+macro_rules! check_window_test {
+    ($name:ident, $t_low:expr, $t_high:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let expectations = [
+                read_expectation(Register::T_LOW, $t_low),
+                read_expectation(Register::T_HIGH, $t_high),
+            ];
+            let mut dev = setup(&expectations);
+            assert_eq!($expected, block_on(dev.check_window()).unwrap());
+            dev.destroy().done();
+        }
+    };
+}
+
+check_window_test!(window_is_valid, [0b0100_1011, 0], [0b0101_0000, 0], true);
+check_window_test!(
+    equal_window_is_valid,
+    [0b0101_0000, 0],
+    [0b0101_0000, 0],
+    true
+);
+check_window_test!(
+    negative_window_is_valid,
+    [0b1110_0111, 0],
+    [0b0001_1001, 0],
+    true
+);
+check_window_test!(
+    window_is_inverted,
+    [0b0101_0000, 0],
+    [0b0100_1011, 0],
+    false
+);
+
+#[test]
+fn can_read_config_verbose() {
+    let expectations = get_expectation(
+        Register::CONFIG,
+        DEFAULT_CONFIG_LSB | BFL::FAULT_QUEUE1 | BFL::ALERT_POLARITY | BFL::THERMOSTAT,
+        BFH::ALERT | BFH::CONV_RATE0 | BFH::EXTENDED_MODE,
+    );
+    let mut dev = setup(&expectations);
+    let (raw, config) = block_on(dev.read_config_verbose()).unwrap();
+    assert_eq!(0b0111_0000_0111_0110, raw);
+    assert_eq!(raw, config.bits());
+    assert_eq!(ConversionRate::_1Hz, config.conversion_rate());
+    assert!(config.is_extended_mode());
+    assert_eq!(FaultQueue::_4, config.fault_queue());
+    assert_eq!(AlertPolarity::ActiveHigh, config.alert_polarity());
+    assert_eq!(ThermostatMode::Interrupt, config.thermostat_mode());
+    dev.destroy().done();
+}
+
+macro_rules! sign_magnitude_test {
+    ($name:ident, $lsb:expr, $msb:expr, $negative:expr, $magnitude:expr) => {
+        #[test]
+        fn $name() {
+            let mut dev = setup(&get_expectation(Register::TEMPERATURE, $lsb, $msb));
+            let (is_negative, magnitude) = block_on(dev.read_temperature_sign_magnitude()).unwrap();
+            assert_eq!($negative, is_negative);
+            assert_near!($magnitude, magnitude);
+            dev.destroy().done();
+        }
+    };
+}
+
+sign_magnitude_test!(
+    can_read_positive_sign_magnitude,
+    0b0001_0000,
+    0b0001_1001,
+    false,
+    25.0625
+);
+sign_magnitude_test!(
+    can_read_negative_sign_magnitude,
+    0b1111_0000,
+    0b1111_0101,
+    true,
+    10.0625
+);
+sign_magnitude_test!(can_read_zero_sign_magnitude, 0, 0, false, 0.0);
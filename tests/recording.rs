@@ -0,0 +1,72 @@
+#![cfg(feature = "test-util")]
+#![feature(async_fn_in_trait)]
+#![allow(incomplete_features)]
+extern crate embedded_hal_mock as hal;
+extern crate tmp1x2;
+use hal::i2c::Transaction as I2cTransaction;
+use tmp1x2::{RecordedTransaction, RecordingWrapper, SlaveAddr, Tmp1x2};
+
+mod common;
+use common::{
+    block_on, BitFlagsHigh as BFH, I2cMock, Register, DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB,
+    DEVICE_ADDRESS,
+};
+
+#[test]
+fn records_config_write_and_temperature_read() {
+    let extended_msb = DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE;
+    let expectations = [
+        I2cTransaction::write(
+            DEVICE_ADDRESS,
+            vec![Register::CONFIG, extended_msb, DEFAULT_CONFIG_LSB],
+        ),
+        I2cTransaction::write_read(
+            DEVICE_ADDRESS,
+            vec![Register::TEMPERATURE],
+            vec![0b0011_0010, 0b0000_0001],
+        ),
+    ];
+    let i2c: RecordingWrapper<_, 4> = RecordingWrapper::new(I2cMock::new(&expectations));
+    let mut dev = Tmp1x2::new(i2c, SlaveAddr::default());
+    block_on(dev.enable_extended_mode()).unwrap();
+    block_on(dev.read_temperature()).unwrap();
+    let i2c = dev.destroy();
+    assert_eq!(
+        &[
+            RecordedTransaction::write(
+                DEVICE_ADDRESS,
+                &[Register::CONFIG, extended_msb, DEFAULT_CONFIG_LSB]
+            ),
+            RecordedTransaction::write_read(
+                DEVICE_ADDRESS,
+                &[Register::TEMPERATURE],
+                &[0b0011_0010, 0b0000_0001]
+            ),
+        ],
+        i2c.transactions()
+    );
+    i2c.destroy().done();
+}
+
+#[test]
+fn stops_recording_when_full() {
+    let expectations = [
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::TEMPERATURE]),
+        I2cTransaction::write(DEVICE_ADDRESS, vec![Register::CONFIG]),
+    ];
+    let i2c: RecordingWrapper<_, 1> = RecordingWrapper::new(I2cMock::new(&expectations));
+    let mut dev = Tmp1x2::new(i2c, SlaveAddr::default());
+    block_on(dev.set_pointer(tmp1x2::Register::Temperature)).unwrap();
+    block_on(dev.set_pointer(tmp1x2::Register::Config)).unwrap();
+    let mut i2c = dev.destroy();
+    assert_eq!(
+        &[RecordedTransaction::write(
+            DEVICE_ADDRESS,
+            &[Register::TEMPERATURE]
+        )],
+        i2c.transactions()
+    );
+    i2c.clear();
+    assert!(i2c.transactions().is_empty());
+    i2c.destroy().done();
+}
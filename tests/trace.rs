@@ -0,0 +1,62 @@
+#![cfg(feature = "trace")]
+#![feature(async_fn_in_trait)]
+#![allow(incomplete_features)]
+extern crate embedded_hal_mock as hal;
+extern crate tmp1x2;
+use hal::i2c::Transaction as I2cTransaction;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+
+mod common;
+use common::{
+    block_on, setup, BitFlagsHigh as BFH, Register, DEFAULT_CONFIG_LSB, DEFAULT_CONFIG_MSB,
+    DEVICE_ADDRESS,
+};
+
+struct CapturingLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Trace
+    }
+
+    fn log(&self, record: &Record) {
+        if record.target().starts_with("tmp1x2") {
+            self.lines
+                .lock()
+                .unwrap()
+                .push(format!("{}", record.args()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    lines: Mutex::new(Vec::new()),
+};
+
+#[test]
+fn logs_config_write() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+    let expected_msb = DEFAULT_CONFIG_MSB | BFH::EXTENDED_MODE;
+    let expectations = [I2cTransaction::write(
+        DEVICE_ADDRESS,
+        vec![Register::CONFIG, expected_msb, DEFAULT_CONFIG_LSB],
+    )];
+    let mut dev = setup(&expectations);
+    block_on(dev.enable_extended_mode()).unwrap();
+    dev.destroy().done();
+    let lines = LOGGER.lines.lock().unwrap();
+    assert_eq!(
+        *lines,
+        [format!(
+            "write to {:?}: {:?}",
+            DEVICE_ADDRESS,
+            [Register::CONFIG, expected_msb, DEFAULT_CONFIG_LSB]
+        )]
+    );
+}